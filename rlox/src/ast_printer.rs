@@ -0,0 +1,332 @@
+use std::rc::Rc;
+
+use crate::{
+    grammar::{
+        AssignExpr, BinaryExpr, BlockStmt, BreakStmt, CallExpr, ClassStmt, ContinueStmt, Expr,
+        ExpressionStmt, FunctionExpr, FunctionStmt, GetExpr, GroupingExpr, IfStmt, IndexExpr,
+        ListExpr, LiteralExpr, LogicalExpr, PrintStmt, ReturnStmt, SetExpr, Stmt, SuperExpr,
+        SwitchStmt, TernaryExpr, ThisExpr, UnaryExpr, UpdateExpr, VarStmt, VariableExpr, WhileStmt,
+    },
+    visitor::{StmtVisitor, Visitor},
+};
+
+// Renders the syntax tree in a Lisp-like form, e.g. `(* (- 1) (group 2))`.
+pub struct AstPrinter;
+
+impl AstPrinter {
+    pub fn print(&mut self, expr: &Expr) -> String {
+        self.visit_expr(expr)
+    }
+
+    pub fn print_stmt(&mut self, stmt: &Stmt) -> String {
+        self.visit_stmt(stmt)
+    }
+
+    fn print_function(&mut self, kind: &str, function: &FunctionStmt) -> String {
+        let params = function
+            .params
+            .iter()
+            .map(|param| param.lexeme.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let header = match function.name.lexeme.as_str() {
+            "" => format!("{} ({})", kind, params),
+            name => format!("{} {} ({})", kind, name, params),
+        };
+        self.print_block(&header, &function.body)
+    }
+
+    fn print_block(&mut self, name: &str, stmts: &[Stmt]) -> String {
+        let mut result = format!("({}", name);
+        for stmt in stmts {
+            result.push_str(&format!(" {}", self.print_stmt(stmt)));
+        }
+        result.push(')');
+        result
+    }
+
+    fn parenthesize(&mut self, name: &str, exprs: &[&Expr]) -> String {
+        let mut result = format!("({}", name);
+        for expr in exprs {
+            result.push_str(&format!(" {}", self.visit_expr(expr)));
+        }
+        result.push(')');
+        result
+    }
+}
+
+impl StmtVisitor<String> for AstPrinter {
+    fn visit_stmt(&mut self, stmt: &Stmt) -> String {
+        match stmt {
+            Stmt::ExpressionStmt(stmt) => self.visit_expression_stmt(stmt),
+            Stmt::PrintStmt(stmt) => self.visit_print_stmt(stmt),
+            Stmt::VarStmt(stmt) => self.visit_var_stmt(stmt),
+            Stmt::BlockStmt(stmt) => self.visit_block_stmt(stmt),
+            Stmt::IfStmt(stmt) => self.visit_if_stmt(stmt),
+            Stmt::WhileStmt(stmt) => self.visit_while_stmt(stmt),
+            Stmt::FunctionStmt(stmt) => self.visit_function_stmt(stmt),
+            Stmt::ReturnStmt(stmt) => self.visit_return_stmt(stmt),
+            Stmt::BreakStmt(stmt) => self.visit_break_stmt(stmt),
+            Stmt::ContinueStmt(stmt) => self.visit_continue_stmt(stmt),
+            Stmt::ClassStmt(stmt) => self.visit_class_stmt(stmt),
+            Stmt::SwitchStmt(stmt) => self.visit_switch_stmt(stmt),
+        }
+    }
+
+    fn visit_expression_stmt(&mut self, stmt: &ExpressionStmt) -> String {
+        self.parenthesize(";", &[&stmt.0])
+    }
+
+    fn visit_print_stmt(&mut self, stmt: &PrintStmt) -> String {
+        self.parenthesize("print", &[&stmt.0])
+    }
+
+    fn visit_var_stmt(&mut self, stmt: &VarStmt) -> String {
+        match &stmt.initializer {
+            Some(initializer) => {
+                self.parenthesize(&format!("var {}", stmt.name.lexeme), &[initializer])
+            }
+            None => format!("(var {})", stmt.name.lexeme),
+        }
+    }
+
+    fn visit_block_stmt(&mut self, stmt: &BlockStmt) -> String {
+        self.print_block("block", &stmt.0)
+    }
+
+    fn visit_if_stmt(&mut self, stmt: &IfStmt) -> String {
+        let mut result = format!(
+            "(if {} {}",
+            self.print(&stmt.condition),
+            self.visit_stmt(&stmt.then_branch)
+        );
+        if let Some(else_branch) = &stmt.else_branch {
+            result.push_str(&format!(" {}", self.visit_stmt(else_branch)));
+        }
+        result.push(')');
+        result
+    }
+
+    fn visit_while_stmt(&mut self, stmt: &WhileStmt) -> String {
+        let mut result = format!(
+            "(while {} {}",
+            self.print(&stmt.condition),
+            self.visit_stmt(&stmt.body)
+        );
+        if let Some(increment) = &stmt.increment {
+            result.push_str(&format!(" {}", self.print(increment)));
+        }
+        result.push(')');
+        result
+    }
+
+    fn visit_function_stmt(&mut self, stmt: &Rc<FunctionStmt>) -> String {
+        self.print_function("fun", stmt)
+    }
+
+    fn visit_return_stmt(&mut self, stmt: &ReturnStmt) -> String {
+        match &stmt.value {
+            Some(value) => self.parenthesize("return", &[value]),
+            None => "(return)".to_string(),
+        }
+    }
+
+    fn visit_break_stmt(&mut self, _: &BreakStmt) -> String {
+        "(break)".to_string()
+    }
+
+    fn visit_continue_stmt(&mut self, _: &ContinueStmt) -> String {
+        "(continue)".to_string()
+    }
+
+    fn visit_class_stmt(&mut self, stmt: &ClassStmt) -> String {
+        let mut result = format!("(class {}", stmt.name.lexeme);
+        if let Some(superclass) = &stmt.superclass {
+            result.push_str(&format!(" < {}", superclass.0.lexeme));
+        }
+        for method in &stmt.methods {
+            let kind = if method.getter { "getter" } else { "method" };
+            result.push_str(&format!(" {}", self.print_function(kind, method)));
+        }
+        for method in &stmt.class_methods {
+            let kind = if method.getter {
+                "class getter"
+            } else {
+                "class method"
+            };
+            result.push_str(&format!(" {}", self.print_function(kind, method)));
+        }
+        result.push(')');
+        result
+    }
+
+    fn visit_switch_stmt(&mut self, stmt: &SwitchStmt) -> String {
+        let mut result = format!("(switch {}", self.print(&stmt.discriminant));
+        for case in &stmt.cases {
+            let name = format!("case {}", self.print(&case.value));
+            result.push_str(&format!(" {}", self.print_block(&name, &case.body)));
+        }
+        if let Some(default) = &stmt.default {
+            result.push_str(&format!(" {}", self.print_block("default", default)));
+        }
+        result.push(')');
+        result
+    }
+}
+
+impl Visitor<String> for AstPrinter {
+    fn visit_expr(&mut self, expr: &Expr) -> String {
+        use Expr::*;
+
+        match expr {
+            LiteralExpr(lexpr) => self.visit_literal_expr(lexpr),
+            UnaryExpr(uexpr) => self.visit_unary_expr(uexpr),
+            BinaryExpr(bexpr) => self.visit_binary_expr(bexpr),
+            GroupingExpr(gexpr) => self.visit_grouping_expr(gexpr),
+            VariableExpr(vexpr) => self.visit_variable_expr(vexpr),
+            AssignExpr(aexpr) => self.visit_assign_expr(aexpr),
+            LogicalExpr(lexpr) => self.visit_logical_expr(lexpr),
+            CallExpr(cexpr) => self.visit_call_expr(cexpr),
+            GetExpr(gexpr) => self.visit_get_expr(gexpr),
+            SetExpr(sexpr) => self.visit_set_expr(sexpr),
+            ThisExpr(texpr) => self.visit_this_expr(texpr),
+            SuperExpr(sexpr) => self.visit_super_expr(sexpr),
+            TernaryExpr(texpr) => self.visit_ternary_expr(texpr),
+            FunctionExpr(fexpr) => self.visit_function_expr(fexpr),
+            ListExpr(lexpr) => self.visit_list_expr(lexpr),
+            IndexExpr(iexpr) => self.visit_index_expr(iexpr),
+            UpdateExpr(uexpr) => self.visit_update_expr(uexpr),
+        }
+    }
+
+    fn visit_literal_expr(&mut self, expr: &LiteralExpr) -> String {
+        expr.0.lexeme.clone()
+    }
+
+    fn visit_unary_expr(&mut self, expr: &UnaryExpr) -> String {
+        self.parenthesize(&expr.op.lexeme, &[&expr.expr])
+    }
+
+    fn visit_binary_expr(&mut self, expr: &BinaryExpr) -> String {
+        self.parenthesize(&expr.op.lexeme, &[&expr.left, &expr.right])
+    }
+
+    fn visit_grouping_expr(&mut self, expr: &GroupingExpr) -> String {
+        self.parenthesize("group", &[&expr.expr])
+    }
+
+    fn visit_variable_expr(&mut self, expr: &VariableExpr) -> String {
+        expr.0.lexeme.clone()
+    }
+
+    fn visit_assign_expr(&mut self, expr: &AssignExpr) -> String {
+        self.parenthesize(&format!("= {}", expr.name.lexeme), &[&expr.value])
+    }
+
+    fn visit_logical_expr(&mut self, expr: &LogicalExpr) -> String {
+        self.parenthesize(&expr.op.lexeme, &[&expr.left, &expr.right])
+    }
+
+    fn visit_call_expr(&mut self, expr: &CallExpr) -> String {
+        let mut exprs = vec![expr.callee.as_ref()];
+        exprs.extend(expr.args.iter());
+        self.parenthesize("call", &exprs)
+    }
+
+    fn visit_get_expr(&mut self, expr: &GetExpr) -> String {
+        let object = self.visit_expr(&expr.object);
+        format!("(. {} {})", object, expr.name.lexeme)
+    }
+
+    fn visit_set_expr(&mut self, expr: &SetExpr) -> String {
+        let object = self.visit_expr(&expr.object);
+        let value = self.visit_expr(&expr.value);
+        format!("(= (. {} {}) {})", object, expr.name.lexeme, value)
+    }
+
+    fn visit_this_expr(&mut self, expr: &ThisExpr) -> String {
+        expr.0.lexeme.clone()
+    }
+
+    fn visit_super_expr(&mut self, expr: &SuperExpr) -> String {
+        format!("(super {})", expr.method.lexeme)
+    }
+
+    fn visit_ternary_expr(&mut self, expr: &TernaryExpr) -> String {
+        self.parenthesize("?:", &[&expr.condition, &expr.then_expr, &expr.else_expr])
+    }
+
+    fn visit_function_expr(&mut self, expr: &FunctionExpr) -> String {
+        self.print_function("fun", &expr.0)
+    }
+
+    fn visit_list_expr(&mut self, expr: &ListExpr) -> String {
+        self.parenthesize("list", &expr.elements.iter().collect::<Vec<_>>())
+    }
+
+    fn visit_index_expr(&mut self, expr: &IndexExpr) -> String {
+        self.parenthesize("index", &[&expr.object, &expr.index])
+    }
+
+    fn visit_update_expr(&mut self, expr: &UpdateExpr) -> String {
+        let fixity = if expr.prefix { "pre" } else { "post" };
+        format!("({}{} {})", fixity, expr.op.lexeme, expr.name.lexeme)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error_reporter::ErrorReporter, parser::Parser, scanner::Scanner};
+
+    #[test]
+    fn print_arithmetic() {
+        let mut error_reporter = ErrorReporter::new();
+        let tokens = Scanner::new("-123 * (45.67)".to_string(), &mut error_reporter).scan_tokens();
+        let expr = Parser::new(&tokens).parse().unwrap();
+
+        assert_eq!(AstPrinter.print(&expr), "(* (- 123) (group 45.67))");
+    }
+
+    #[test]
+    fn print_switch_statement() {
+        let mut error_reporter = ErrorReporter::new();
+        let source = "switch (x) { case 1: print 1; default: print 2; }";
+        let tokens = Scanner::new(source.to_string(), &mut error_reporter).scan_tokens();
+        let stmts = Parser::new(&tokens).parse_program().unwrap();
+
+        assert_eq!(
+            AstPrinter.visit_stmt(&stmts[0]),
+            "(switch x (case 1 (print 1)) (default (print 2)))"
+        );
+    }
+
+    #[test]
+    fn print_print_statement() {
+        let mut error_reporter = ErrorReporter::new();
+        let tokens = Scanner::new("print 1;".to_string(), &mut error_reporter).scan_tokens();
+        let stmts = Parser::new(&tokens).parse_program().unwrap();
+
+        assert_eq!(AstPrinter.visit_stmt(&stmts[0]), "(print 1)");
+    }
+
+    #[test]
+    fn print_statements() {
+        let mut error_reporter = ErrorReporter::new();
+        let source = "var a = 1; if (a < 2) print a.b(3); else { a = nil; }";
+        let tokens = Scanner::new(source.to_string(), &mut error_reporter).scan_tokens();
+        let stmts = Parser::new(&tokens).parse_program().unwrap();
+
+        let printed = stmts
+            .iter()
+            .map(|stmt| AstPrinter.print_stmt(stmt))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            printed,
+            vec![
+                "(var a 1)",
+                "(if (< a 2) (print (call (. a b) 3)) (block (; (= a nil))))",
+            ]
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use std::{
+    cell::RefCell,
+    fmt::{Debug, Display},
+    rc::Rc,
+};
+
+use crate::{
+    class::LoxInstance,
+    environment::Environment,
+    error_reporter::RuntimeError,
+    grammar::{FunctionStmt, Literal},
+    interpreter::{ControlFlow, Interpreter},
+};
+
+// How many arguments a callable takes.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Arity {
+    Exact(usize),
+    AtLeast(usize),
+    // Inclusive at both ends.
+    Range(usize, usize),
+}
+
+impl Arity {
+    pub fn accepts(&self, count: usize) -> bool {
+        match *self {
+            Arity::Exact(n) => count == n,
+            Arity::AtLeast(min) => count >= min,
+            Arity::Range(min, max) => (min..=max).contains(&count),
+        }
+    }
+}
+
+// Reads as the object of "Expected ...", e.g. "at least 1 argument".
+impl Display for Arity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let noun = |n: usize| if n == 1 { "argument" } else { "arguments" };
+        match *self {
+            Arity::Exact(n) => write!(f, "{} {}", n, noun(n)),
+            Arity::AtLeast(min) => write!(f, "at least {} {}", min, noun(min)),
+            Arity::Range(min, max) => write!(f, "{} to {} {}", min, max, noun(max)),
+        }
+    }
+}
+
+pub trait LoxCallable: Display {
+    fn arity(&self) -> Arity;
+    // Takes an owned handle so a class can give its instances a reference
+    // back to itself. `line` is where the call appears, for errors raised
+    // by the callee itself.
+    fn call(
+        self: Rc<Self>,
+        interpreter: &mut Interpreter,
+        arguments: Vec<Literal>,
+        line: i32,
+    ) -> Result<Literal, RuntimeError>;
+}
+
+// Callables are compared by identity and printed by name, which lets
+// `Literal` keep deriving `PartialEq` and `Debug`.
+impl PartialEq for dyn LoxCallable {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::addr_eq(self, other)
+    }
+}
+
+impl Debug for dyn LoxCallable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+pub struct LoxFunction {
+    declaration: Rc<FunctionStmt>,
+    closure: Rc<RefCell<Environment>>,
+    // Initializers always hand back `this`, even on a bare `return;`.
+    is_initializer: bool,
+}
+
+impl LoxFunction {
+    pub fn new(
+        declaration: Rc<FunctionStmt>,
+        closure: Rc<RefCell<Environment>>,
+        is_initializer: bool,
+    ) -> LoxFunction {
+        LoxFunction {
+            declaration,
+            closure,
+            is_initializer,
+        }
+    }
+
+    // What accessing this method yields: the result of running it for a
+    // getter, or the method itself otherwise.
+    pub fn get_or_call(
+        self: Rc<Self>,
+        interpreter: &mut Interpreter,
+        line: i32,
+    ) -> Result<Literal, RuntimeError> {
+        if self.declaration.getter {
+            return self.call(interpreter, Vec::new(), line);
+        }
+
+        Ok(Literal::Callable(self))
+    }
+
+    // Creates a copy of this method whose scope has `this` bound to `instance`.
+    pub fn bind(&self, instance: Rc<RefCell<LoxInstance>>) -> LoxFunction {
+        let mut environment = Environment::with_enclosing(Rc::clone(&self.closure));
+        environment.define("this".to_string(), Literal::Instance(instance));
+
+        LoxFunction::new(
+            Rc::clone(&self.declaration),
+            Rc::new(RefCell::new(environment)),
+            self.is_initializer,
+        )
+    }
+}
+
+impl LoxCallable for LoxFunction {
+    fn arity(&self) -> Arity {
+        Arity::Exact(self.declaration.params.len())
+    }
+
+    fn call(
+        self: Rc<Self>,
+        interpreter: &mut Interpreter,
+        arguments: Vec<Literal>,
+        _line: i32,
+    ) -> Result<Literal, RuntimeError> {
+        let mut environment = Environment::with_enclosing(Rc::clone(&self.closure));
+        for (param, argument) in self.declaration.params.iter().zip(arguments) {
+            environment.define(param.lexeme.clone(), argument);
+        }
+
+        let flow = interpreter
+            .execute_block(&self.declaration.body, Rc::new(RefCell::new(environment)))?;
+        if self.is_initializer {
+            return Ok(self
+                .closure
+                .borrow()
+                .lookup("this")
+                .expect("initializers are always bound to an instance"));
+        }
+
+        match flow {
+            ControlFlow::Return(value) => Ok(value),
+            // The parser rejects `break` and `continue` outside of loops, so
+            // they never escape a function body.
+            ControlFlow::Normal | ControlFlow::Break | ControlFlow::Continue => Ok(Literal::Nil),
+        }
+    }
+}
+
+impl Display for LoxFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.declaration.name.lexeme.as_str() {
+            "" => write!(f, "<fn>"),
+            name => write!(f, "<fn {}>", name),
+        }
+    }
+}
+
+pub type NativeFn = fn(&mut Interpreter, Vec<Literal>, i32) -> Result<Literal, RuntimeError>;
+
+// A built-in function implemented in Rust.
+pub struct NativeFunction {
+    name: &'static str,
+    arity: Arity,
+    function: NativeFn,
+}
+
+impl NativeFunction {
+    pub fn new(name: &'static str, arity: Arity, function: NativeFn) -> NativeFunction {
+        NativeFunction {
+            name,
+            arity,
+            function,
+        }
+    }
+}
+
+impl LoxCallable for NativeFunction {
+    fn arity(&self) -> Arity {
+        self.arity
+    }
+
+    fn call(
+        self: Rc<Self>,
+        interpreter: &mut Interpreter,
+        arguments: Vec<Literal>,
+        line: i32,
+    ) -> Result<Literal, RuntimeError> {
+        (self.function)(interpreter, arguments, line)
+    }
+}
+
+impl Display for NativeFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "<native fn {}>", self.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_arity() {
+        let arity = Arity::Exact(2);
+
+        assert!(arity.accepts(2));
+        assert!(!arity.accepts(1));
+        assert!(!arity.accepts(3));
+        assert_eq!(arity.to_string(), "2 arguments");
+        assert_eq!(Arity::Exact(1).to_string(), "1 argument");
+    }
+
+    #[test]
+    fn at_least_arity() {
+        let arity = Arity::AtLeast(1);
+
+        assert!(!arity.accepts(0));
+        assert!(arity.accepts(1));
+        assert!(arity.accepts(10));
+        assert_eq!(arity.to_string(), "at least 1 argument");
+    }
+
+    #[test]
+    fn range_arity() {
+        let arity = Arity::Range(1, 2);
+
+        assert!(!arity.accepts(0));
+        assert!(arity.accepts(1));
+        assert!(arity.accepts(2));
+        assert!(!arity.accepts(3));
+        assert_eq!(arity.to_string(), "1 to 2 arguments");
+    }
+}
@@ -0,0 +1,179 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt::{Debug, Display},
+    rc::Rc,
+};
+
+use crate::{
+    callable::{Arity, LoxCallable, LoxFunction},
+    error_reporter::{RuntimeError, RuntimeErrorKind},
+    grammar::Literal,
+    interpreter::Interpreter,
+    token::Token,
+};
+
+pub struct LoxClass {
+    pub name: String,
+    superclass: Option<Rc<LoxClass>>,
+    methods: HashMap<String, Rc<LoxFunction>>,
+    // Methods declared with `class`, called on the class itself and never
+    // bound to an instance.
+    class_methods: HashMap<String, Rc<LoxFunction>>,
+}
+
+impl LoxClass {
+    pub fn new(
+        name: String,
+        superclass: Option<Rc<LoxClass>>,
+        methods: HashMap<String, Rc<LoxFunction>>,
+        class_methods: HashMap<String, Rc<LoxFunction>>,
+    ) -> LoxClass {
+        LoxClass {
+            name,
+            superclass,
+            methods,
+            class_methods,
+        }
+    }
+
+    // Looks the method up on this class, then up the superclass chain.
+    pub fn find_method(&self, name: &str) -> Option<Rc<LoxFunction>> {
+        if let Some(method) = self.methods.get(name) {
+            return Some(Rc::clone(method));
+        }
+
+        self.superclass
+            .as_ref()
+            .and_then(|superclass| superclass.find_method(name))
+    }
+
+    // Like `find_method`, but for the methods declared with `class`.
+    pub fn find_class_method(&self, name: &str) -> Option<Rc<LoxFunction>> {
+        if let Some(method) = self.class_methods.get(name) {
+            return Some(Rc::clone(method));
+        }
+
+        self.superclass
+            .as_ref()
+            .and_then(|superclass| superclass.find_class_method(name))
+    }
+
+    // Whether this is `class` itself or inherits from it.
+    pub fn is_subclass_of(&self, class: &LoxClass) -> bool {
+        std::ptr::eq(self, class)
+            || self
+                .superclass
+                .as_ref()
+                .is_some_and(|superclass| superclass.is_subclass_of(class))
+    }
+}
+
+impl LoxCallable for LoxClass {
+    // A class takes the same arguments as its initializer.
+    fn arity(&self) -> Arity {
+        self.find_method("init")
+            .map_or(Arity::Exact(0), |initializer| initializer.arity())
+    }
+
+    fn call(
+        self: Rc<Self>,
+        interpreter: &mut Interpreter,
+        arguments: Vec<Literal>,
+        line: i32,
+    ) -> Result<Literal, RuntimeError> {
+        let initializer = self.find_method("init");
+        let instance = Rc::new(RefCell::new(LoxInstance::new(self)));
+
+        if let Some(initializer) = initializer {
+            Rc::new(initializer.bind(Rc::clone(&instance))).call(interpreter, arguments, line)?;
+        }
+
+        Ok(Literal::Instance(instance))
+    }
+}
+
+impl Display for LoxClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+// Classes and instances have identity semantics, like callables.
+impl PartialEq for LoxClass {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl Debug for LoxClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+pub struct LoxInstance {
+    class: Rc<LoxClass>,
+    fields: HashMap<String, Literal>,
+}
+
+impl LoxInstance {
+    pub fn new(class: Rc<LoxClass>) -> LoxInstance {
+        LoxInstance {
+            class,
+            fields: HashMap::new(),
+        }
+    }
+
+    pub fn class(&self) -> &Rc<LoxClass> {
+        &self.class
+    }
+
+    // Fields shadow methods. `instance` is the shared handle to `this`, which
+    // a found method gets bound to. Getters run right away, which is why the
+    // interpreter is needed.
+    pub fn get(
+        instance: &Rc<RefCell<LoxInstance>>,
+        name: &Token,
+        interpreter: &mut Interpreter,
+    ) -> Result<Literal, RuntimeError> {
+        let method = {
+            let this = instance.borrow();
+            if let Some(value) = this.fields.get(&name.lexeme) {
+                return Ok(value.clone());
+            }
+            this.class.find_method(&name.lexeme)
+        };
+
+        if let Some(method) = method {
+            return Rc::new(method.bind(Rc::clone(instance))).get_or_call(interpreter, name.line);
+        }
+
+        Err(RuntimeError::new(
+            name.line,
+            RuntimeErrorKind::UndefinedProperty(name.lexeme.clone()),
+        ))
+    }
+
+    pub fn set(&mut self, name: &Token, value: Literal) {
+        self.fields.insert(name.lexeme.clone(), value);
+    }
+}
+
+impl Display for LoxInstance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} instance", self.class.name)
+    }
+}
+
+impl PartialEq for LoxInstance {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl Debug for LoxInstance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
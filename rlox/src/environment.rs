@@ -0,0 +1,110 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::{
+    error_reporter::{RuntimeError, RuntimeErrorKind},
+    grammar::Literal,
+    token::Token,
+};
+
+#[derive(Default)]
+pub struct Environment {
+    values: HashMap<String, Literal>,
+    // Shared so that closures can keep their defining scope alive.
+    enclosing: Option<Rc<RefCell<Environment>>>,
+}
+
+impl Environment {
+    pub fn new() -> Environment {
+        Environment {
+            values: HashMap::new(),
+            enclosing: None,
+        }
+    }
+
+    pub fn with_enclosing(enclosing: Rc<RefCell<Environment>>) -> Environment {
+        Environment {
+            values: HashMap::new(),
+            enclosing: Some(enclosing),
+        }
+    }
+
+    pub fn define(&mut self, name: String, value: Literal) {
+        self.values.insert(name, value);
+    }
+
+    pub fn get(&self, name: &Token) -> Result<Literal, RuntimeError> {
+        self.lookup(&name.lexeme)
+            .ok_or_else(|| undefined_variable(name))
+    }
+
+    pub fn lookup(&self, name: &str) -> Option<Literal> {
+        if let Some(value) = self.values.get(name) {
+            return Some(value.clone());
+        }
+
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow().lookup(name),
+            None => None,
+        }
+    }
+
+    // Reads `name` from exactly `distance` scopes out, as worked out by the
+    // resolver, without searching.
+    pub fn get_at(&self, distance: usize, name: &Token) -> Result<Literal, RuntimeError> {
+        if distance == 0 {
+            return self
+                .values
+                .get(&name.lexeme)
+                .cloned()
+                .ok_or_else(|| undefined_variable(name));
+        }
+
+        self.enclosing
+            .as_ref()
+            .expect("Resolved scopes always exist.")
+            .borrow()
+            .get_at(distance - 1, name)
+    }
+
+    pub fn assign_at(
+        &mut self,
+        distance: usize,
+        name: &Token,
+        value: Literal,
+    ) -> Result<(), RuntimeError> {
+        if distance == 0 {
+            return match self.values.get_mut(&name.lexeme) {
+                Some(slot) => {
+                    *slot = value;
+                    Ok(())
+                }
+                None => Err(undefined_variable(name)),
+            };
+        }
+
+        self.enclosing
+            .as_ref()
+            .expect("Resolved scopes always exist.")
+            .borrow_mut()
+            .assign_at(distance - 1, name, value)
+    }
+
+    pub fn assign(&mut self, name: &Token, value: Literal) -> Result<(), RuntimeError> {
+        if let Some(slot) = self.values.get_mut(&name.lexeme) {
+            *slot = value;
+            return Ok(());
+        }
+
+        match &self.enclosing {
+            Some(enclosing) => enclosing.borrow_mut().assign(name, value),
+            None => Err(undefined_variable(name)),
+        }
+    }
+}
+
+fn undefined_variable(name: &Token) -> RuntimeError {
+    RuntimeError::new(
+        name.line,
+        RuntimeErrorKind::UndefinedVariable(name.lexeme.clone()),
+    )
+}
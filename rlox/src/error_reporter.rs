@@ -1,56 +1,619 @@
+use std::{
+    fmt::{self, Display},
+    io::{self, Write},
+    num::NonZeroUsize,
+};
+
 use exitcode::ExitCode;
 
-use crate::token::{Token, TokenType};
+use crate::{callable::Arity, interpreter::MAX_PRINT_PRECISION, token::TokenType};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "Error"),
+            Severity::Warning => write!(f, "Warning"),
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Diagnostic {
+    pub line: i32,
+    // Runtime errors don't know their column yet.
+    pub column: Option<usize>,
+    pub message: String,
+    pub severity: Severity,
+}
+
+// Where a diagnostic points within its line.
+#[derive(Clone, Copy)]
+struct Position {
+    column: usize,
+    // Where `column` is displayed once tabs before it are expanded, worked
+    // out by the scanner.
+    visual_column: usize,
+}
 
 pub struct ErrorReporter {
     pub had_error: bool,
     pub exit_code: Option<ExitCode>,
+    // Lines of the source being run, used to point at the offending character.
+    source_lines: Vec<String>,
+    // How wide tabs are drawn, both in quoted source lines and by the scanner
+    // when it works out visual columns.
+    tab_width: usize,
+    // Every diagnostic reported so far, in order.
+    diagnostics: Vec<Diagnostic>,
+    // Where diagnostics are written; stderr unless redirected.
+    output: Box<dyn Write>,
 }
 
 impl ErrorReporter {
     pub fn new() -> ErrorReporter {
+        Self::with_output(Box::new(io::stderr()))
+    }
+
+    pub fn with_output(output: Box<dyn Write>) -> ErrorReporter {
         ErrorReporter {
             had_error: false,
             exit_code: None,
+            source_lines: Vec::new(),
+            tab_width: DEFAULT_TAB_WIDTH,
+            diagnostics: Vec::new(),
+            output,
+        }
+    }
+
+    pub fn with_source(source: &str) -> ErrorReporter {
+        ErrorReporter {
+            source_lines: source.lines().map(String::from).collect(),
+            ..ErrorReporter::new()
         }
     }
 
-    pub fn error(&mut self, line: i32, message: &str, exit_code: ExitCode) {
-        self.report(line, "", message, exit_code);
+    // Points later reports at `source`, so they can quote the offending line.
+    pub fn set_source(&mut self, source: &str) {
+        self.source_lines = source.lines().map(String::from).collect();
+    }
+
+    // Zero isn't allowed, since tabs couldn't advance to any tab stop.
+    pub fn set_tab_width(&mut self, tab_width: NonZeroUsize) {
+        self.tab_width = tab_width.get();
+    }
+
+    pub fn tab_width(&self) -> usize {
+        self.tab_width
+    }
+
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
     }
 
-    pub fn parse_error(&mut self, token: &Token, message: &str, exit_code: ExitCode) {
-        if token.typ == TokenType::EOF {
-            self.report(token.line, " at end", message, exit_code);
-        } else {
-            self.report(
-                token.line,
-                format!(" at '{}'", token.lexeme).as_str(),
-                message,
-                exit_code,
-            );
+    // Writes a closing line such as "3 errors" once reporting is done.
+    pub fn report_summary(&mut self) {
+        let count = self
+            .diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Error)
+            .count();
+        if count == 0 {
+            return;
         }
+
+        let noun = if count == 1 { "error" } else { "errors" };
+        writeln!(self.output, "{} {}", count, noun).expect("Failed to write error report.");
+    }
+
+    pub fn error(
+        &mut self,
+        line: i32,
+        column: usize,
+        visual_column: usize,
+        message: &str,
+        exit_code: ExitCode,
+    ) {
+        let position = Position {
+            column,
+            visual_column,
+        };
+        self.report(line, Some(position), "", message, exit_code);
+    }
+
+    pub fn scan_error(&mut self, error: &ScanError) {
+        let position = Position {
+            column: error.column,
+            visual_column: error.visual_column,
+        };
+        self.report(
+            error.line,
+            Some(position),
+            "",
+            &error.message,
+            error.exit_code,
+        );
+    }
+
+    pub fn parse_error(&mut self, error: &ParseError) {
+        let _where = match &error.lexeme {
+            Some(lexeme) => format!(" at '{}'", lexeme),
+            None => " at end".to_string(),
+        };
+        let position = Position {
+            column: error.column,
+            visual_column: error.visual_column,
+        };
+        self.report(
+            error.line,
+            Some(position),
+            &_where,
+            &error.message,
+            error.exit_code,
+        );
+    }
+
+    // Reports a problem that doesn't stop the program from running.
+    pub fn warn(&mut self, line: i32, column: usize, visual_column: usize, message: &str) {
+        let position = Position {
+            column,
+            visual_column,
+        };
+        self.emit(Severity::Warning, line, Some(position), "", message);
     }
 
     pub fn runtime_error(&mut self, line: i32, message: &str, exit_code: ExitCode) {
-        self.report(line, "", message, exit_code);
+        self.report(line, None, "", message, exit_code);
     }
 
-    fn report(&mut self, line: i32, _where: &str, message: &str, exit_code: ExitCode) {
-        println!("[line {}] Error{}: {}", line, _where, message);
+    fn report(
+        &mut self,
+        line: i32,
+        position: Option<Position>,
+        _where: &str,
+        message: &str,
+        exit_code: ExitCode,
+    ) {
+        self.emit(Severity::Error, line, position, _where, message);
         self.had_error = true;
         self.exit_code = Some(exit_code);
     }
+
+    fn emit(
+        &mut self,
+        severity: Severity,
+        line: i32,
+        position: Option<Position>,
+        _where: &str,
+        message: &str,
+    ) {
+        let report = self.format(severity, line, position, _where, message);
+        writeln!(self.output, "{}", report).expect("Failed to write error report.");
+        self.diagnostics.push(Diagnostic {
+            line,
+            column: position.map(|position| position.column),
+            message: message.to_string(),
+            severity,
+        });
+    }
+
+    fn format(
+        &self,
+        severity: Severity,
+        line: i32,
+        position: Option<Position>,
+        _where: &str,
+        message: &str,
+    ) -> String {
+        let mut report = match position {
+            Some(position) => format!(
+                "[line {}, col {}] {}{}: {}",
+                line, position.column, severity, _where, message
+            ),
+            None => format!("[line {}] {}{}: {}", line, severity, _where, message),
+        };
+
+        let Some(source_line) = self.source_lines.get((line - 1) as usize) else {
+            return report;
+        };
+        // Tabs are expanded in the quoted line so the marker, placed by
+        // visual column, lines up with it however the terminal draws them.
+        let source_line = self.expand_tabs(source_line);
+        // Without a column, such as for runtime errors, the whole line is
+        // marked instead of a single character.
+        let (start, width) = match position {
+            Some(position) => (position.visual_column - 1, 1),
+            None => {
+                let code = source_line.trim();
+                if code.is_empty() {
+                    return report;
+                }
+                let start = source_line.chars().count() - source_line.trim_start().chars().count();
+                (start, code.chars().count())
+            }
+        };
+        report.push_str(&format!(
+            "\n{}\n{}{}",
+            source_line,
+            " ".repeat(start),
+            "^".repeat(width)
+        ));
+
+        report
+    }
+
+    fn expand_tabs(&self, line: &str) -> String {
+        let mut expanded = String::new();
+        for c in line.chars() {
+            match c {
+                '\t' => {
+                    let width = self.tab_width - expanded.chars().count() % self.tab_width;
+                    expanded.push_str(&" ".repeat(width));
+                }
+                c => expanded.push(c),
+            }
+        }
+        expanded
+    }
 }
 
+// How many columns a tab advances to, matching most terminals.
+const DEFAULT_TAB_WIDTH: usize = 8;
+
 impl Default for ErrorReporter {
     fn default() -> Self {
         Self::new()
     }
 }
 
-pub struct RuntimeError {
+#[derive(PartialEq, Debug)]
+pub struct ScanError {
+    pub line: i32,
+    pub column: usize,
+    // Where `column` is displayed once tabs before it are expanded, which is
+    // where the report's caret goes.
+    pub visual_column: usize,
+    // The source text scanned for the token that failed, e.g. "@".
+    pub lexeme: String,
+    pub message: String,
+    pub exit_code: ExitCode,
+}
+
+#[derive(PartialEq, Debug)]
+pub struct ParseError {
     pub line: i32,
+    pub column: usize,
+    // Copied from the offending token's `visual_column`.
+    pub visual_column: usize,
+    // The offending lexeme, or `None` when the error is at the end of input.
+    pub lexeme: Option<String>,
     pub message: String,
-    pub exit_code: ExitCode
+    pub exit_code: ExitCode,
+}
+
+#[derive(PartialEq, Debug)]
+pub struct RuntimeError {
+    pub line: i32,
+    pub kind: RuntimeErrorKind,
+    pub exit_code: ExitCode,
+}
+
+impl RuntimeError {
+    pub fn new(line: i32, kind: RuntimeErrorKind) -> RuntimeError {
+        let exit_code = match kind {
+            RuntimeErrorKind::StackOverflow => exitcode::SOFTWARE,
+            RuntimeErrorKind::ReadFailed(_) => exitcode::IOERR,
+            _ => exitcode::DATAERR,
+        };
+        RuntimeError {
+            line,
+            kind,
+            exit_code,
+        }
+    }
+}
+
+// What went wrong at runtime. Embedders can match on this, while `Display`
+// gives the message shown to users. Values are kept as the text the message
+// shows, so errors stay `Send` even though `Literal`s aren't.
+#[derive(PartialEq, Debug)]
+pub enum RuntimeErrorKind {
+    UndefinedVariable(String),
+    UndefinedProperty(String),
+    InvalidOperand {
+        op: TokenType,
+        operand: String,
+    },
+    TypeMismatch {
+        op: TokenType,
+        left: String,
+        right: String,
+    },
+    DivisionByZero,
+    InvalidUpdate,
+    NotAList,
+    NonIntegerIndex,
+    IndexOutOfRange,
+    NotCallable,
+    ArityMismatch {
+        expected: Arity,
+        got: usize,
+    },
+    StackOverflow,
+    NoProperties,
+    NoFields,
+    InvalidSuperclass,
+    // Raised by native functions.
+    LengthOfNonString,
+    InvalidNumber(String),
+    AssertionFailed(String),
+    InvalidAssertionMessage,
+    InvalidPrecision,
+    ReadFailed(String),
+    FormatNonString,
+    FormatArgumentCount {
+        placeholders: usize,
+        got: usize,
+    },
+    // Whether the "maximum" or "minimum" was asked for.
+    ExtremumOfNonNumber(&'static str),
+    InstanceCheckNonClass,
+}
+
+impl Display for RuntimeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use RuntimeErrorKind::*;
+
+        match self {
+            UndefinedVariable(name) => write!(f, "Undefined variable '{}'.", name),
+            UndefinedProperty(name) => write!(f, "Undefined property '{}'.", name),
+            InvalidOperand { op, operand } => {
+                write!(f, "Cannot perform '{:?}' on operand '{}'", op, operand)
+            }
+            TypeMismatch { op, left, right } => write!(
+                f,
+                "Cannot perform '{:?}' on operands '{}' and '{}'",
+                op, left, right
+            ),
+            DivisionByZero => write!(f, "Division by zero."),
+            InvalidUpdate => write!(f, "Can only increment or decrement a number."),
+            NotAList => write!(f, "Can only index into lists."),
+            NonIntegerIndex => write!(f, "List index must be an integer."),
+            IndexOutOfRange => write!(f, "List index out of range."),
+            NotCallable => write!(f, "Can only call functions and classes."),
+            ArityMismatch { expected, got } => write!(f, "Expected {} but got {}.", expected, got),
+            StackOverflow => write!(f, "Stack overflow."),
+            NoProperties => write!(f, "Only instances have properties."),
+            NoFields => write!(f, "Only instances have fields."),
+            InvalidSuperclass => write!(f, "Superclass must be a class."),
+            LengthOfNonString => write!(f, "Can only take the length of a string."),
+            InvalidNumber(value) => write!(f, "Can't convert '{}' to a number.", value),
+            AssertionFailed(message) => write!(f, "{}", message),
+            InvalidAssertionMessage => write!(f, "Assertion message must be a string."),
+            InvalidPrecision => write!(
+                f,
+                "Precision must be an integer from 0 to {} or nil.",
+                MAX_PRINT_PRECISION
+            ),
+            ReadFailed(error) => write!(f, "Could not read input: {}.", error),
+            FormatNonString => write!(f, "Can only format a string."),
+            FormatArgumentCount { placeholders, got } => write!(
+                f,
+                "Format string has {} placeholders but got {} arguments.",
+                placeholders, got
+            ),
+            ExtremumOfNonNumber(what) => write!(f, "Can only take the {} of numbers.", what),
+            InstanceCheckNonClass => write!(f, "Can only check instances of a class."),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::{tests::SharedBuffer, Interpreter};
+
+    #[test]
+    fn report_writes_to_output() {
+        let output = SharedBuffer::default();
+        let mut error_reporter = ErrorReporter::with_output(Box::new(output.clone()));
+        error_reporter.error(3, 5, 5, "Unterminated string.", exitcode::DATAERR);
+
+        assert!(error_reporter.had_error);
+        assert_eq!(
+            output.contents(),
+            "[line 3, col 5] Error: Unterminated string.\n"
+        );
+    }
+
+    #[test]
+    fn warning_does_not_set_had_error() {
+        let output = SharedBuffer::default();
+        let mut error_reporter = ErrorReporter::with_output(Box::new(output.clone()));
+        error_reporter.warn(4, 2, 2, "Unreachable code.");
+
+        assert!(!error_reporter.had_error);
+        assert_eq!(error_reporter.exit_code, None);
+        assert_eq!(error_reporter.diagnostics()[0].severity, Severity::Warning);
+        assert_eq!(
+            output.contents(),
+            "[line 4, col 2] Warning: Unreachable code.\n"
+        );
+    }
+
+    #[test]
+    fn report_summary_counts_errors() {
+        let output = SharedBuffer::default();
+        let mut error_reporter = ErrorReporter::with_output(Box::new(output.clone()));
+        error_reporter.error(1, 1, 1, "Unrecognized character: @", exitcode::DATAERR);
+        error_reporter.error(2, 1, 1, "Unrecognized character: #", exitcode::DATAERR);
+        error_reporter.report_summary();
+
+        assert!(output.contents().ends_with("\n2 errors\n"));
+    }
+
+    #[test]
+    fn format_error_with_caret() {
+        let error_reporter = ErrorReporter::with_source("var x = 1;\nprint x @ 2;");
+        let position = Position {
+            column: 9,
+            visual_column: 9,
+        };
+        let report = error_reporter.format(
+            Severity::Error,
+            2,
+            Some(position),
+            "",
+            "Unrecognized character: @",
+        );
+
+        assert_eq!(
+            report,
+            "[line 2, col 9] Error: Unrecognized character: @\nprint x @ 2;\n        ^"
+        );
+    }
+
+    #[test]
+    fn format_error_with_caret_after_tabs() {
+        let error_reporter = ErrorReporter::with_source("\t\tprint @;");
+        let position = Position {
+            column: 9,
+            visual_column: 23,
+        };
+        let report = error_reporter.format(
+            Severity::Error,
+            1,
+            Some(position),
+            "",
+            "Unrecognized character: @",
+        );
+
+        assert_eq!(
+            report,
+            "[line 1, col 9] Error: Unrecognized character: @\n                print @;\n                      ^"
+        );
+    }
+
+    #[test]
+    fn format_error_with_caret_after_mixed_indentation() {
+        let mut error_reporter = ErrorReporter::with_source("  \tx\t= @;");
+        error_reporter.set_tab_width(NonZeroUsize::new(4).unwrap());
+        let position = Position {
+            column: 8,
+            visual_column: 11,
+        };
+        let report = error_reporter.format(
+            Severity::Error,
+            1,
+            Some(position),
+            "",
+            "Unrecognized character: @",
+        );
+
+        assert_eq!(
+            report,
+            "[line 1, col 8] Error: Unrecognized character: @\n    x   = @;\n          ^"
+        );
+    }
+
+    #[test]
+    fn format_error_without_column() {
+        let error_reporter = ErrorReporter::with_source("\tprint -\"a\";  ");
+        let report =
+            error_reporter.format(Severity::Error, 1, None, "", "Operand must be a number.");
+
+        assert_eq!(
+            report,
+            "[line 1] Error: Operand must be a number.\n        print -\"a\";  \n        ^^^^^^^^^^^"
+        );
+    }
+
+    #[test]
+    fn runtime_error_kind_display_matches_messages() {
+        assert_eq!(
+            RuntimeErrorKind::UndefinedVariable("x".to_string()).to_string(),
+            "Undefined variable 'x'."
+        );
+        assert_eq!(
+            RuntimeErrorKind::DivisionByZero.to_string(),
+            "Division by zero."
+        );
+        assert_eq!(
+            RuntimeErrorKind::ArityMismatch {
+                expected: Arity::AtLeast(1),
+                got: 0
+            }
+            .to_string(),
+            "Expected at least 1 argument but got 0."
+        );
+        assert_eq!(
+            RuntimeErrorKind::TypeMismatch {
+                op: TokenType::Minus,
+                left: "Number(1.0)".to_string(),
+                right: "String(\"a\")".to_string(),
+            }
+            .to_string(),
+            "Cannot perform 'Minus' on operands 'Number(1.0)' and 'String(\"a\")'"
+        );
+    }
+
+    #[test]
+    fn runtime_error_exit_code_follows_kind() {
+        assert_eq!(
+            RuntimeError::new(1, RuntimeErrorKind::NotCallable).exit_code,
+            exitcode::DATAERR
+        );
+        assert_eq!(
+            RuntimeError::new(1, RuntimeErrorKind::StackOverflow).exit_code,
+            exitcode::SOFTWARE
+        );
+    }
+
+    #[test]
+    fn format_error_without_source() {
+        let error_reporter = ErrorReporter::new();
+        let report =
+            error_reporter.format(Severity::Error, 1, None, "", "Operand must be a number.");
+
+        assert_eq!(report, "[line 1] Error: Operand must be a number.");
+    }
+
+    #[test]
+    fn runtime_error_quotes_source_line() {
+        let output = SharedBuffer::default();
+        let mut error_reporter = ErrorReporter::with_output(Box::new(output.clone()));
+        // `1 + "a"` concatenates, so subtract instead to fail.
+        let source = "var a = 1;\nprint a;\nprint 1 - \"a\";";
+        let mut interpreter = Interpreter::with_output(Box::new(io::sink()));
+        let exit_code = crate::run(source, &mut error_reporter, &mut interpreter);
+
+        assert_eq!(exit_code, exitcode::DATAERR);
+        let contents = output.contents();
+        assert!(contents.starts_with("[line 3] Error: "));
+        assert!(contents.ends_with("\nprint 1 - \"a\";\n^^^^^^^^^^^^^^\n"));
+    }
+
+    #[test]
+    fn scan_error_caret_under_tab_indented_glyph() {
+        let output = SharedBuffer::default();
+        let mut error_reporter = ErrorReporter::with_output(Box::new(output.clone()));
+        let source = "if (true) {\n\tprint 1 @ 2;\n}";
+        let mut interpreter = Interpreter::with_output(Box::new(io::sink()));
+        let exit_code = crate::run(source, &mut error_reporter, &mut interpreter);
+
+        assert_eq!(exit_code, exitcode::DATAERR);
+        let report = output.contents();
+        let lines = report.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines[0],
+            "[line 2, col 10] Error: Unrecognized character: @"
+        );
+        assert_eq!(lines[1].find('@'), lines[2].find('^'));
+        assert_eq!(lines[2], "                ^");
+    }
 }
@@ -1,47 +1,492 @@
-use std::fmt::Display;
+use std::{cell::RefCell, fmt::Display, rc::Rc};
 
-use crate::token::Token;
+use serde::{Serialize, Serializer};
 
-#[derive(PartialEq, Debug)]
+use crate::{
+    callable::LoxCallable,
+    class::{LoxClass, LoxInstance},
+    token::Token,
+};
+
+#[derive(PartialEq, Debug, Serialize)]
+#[serde(tag = "type", content = "node")]
 pub enum Expr {
     LiteralExpr(LiteralExpr),
     UnaryExpr(UnaryExpr),
     BinaryExpr(BinaryExpr),
     GroupingExpr(GroupingExpr),
+    VariableExpr(VariableExpr),
+    AssignExpr(AssignExpr),
+    LogicalExpr(LogicalExpr),
+    CallExpr(CallExpr),
+    GetExpr(GetExpr),
+    SetExpr(SetExpr),
+    ThisExpr(ThisExpr),
+    SuperExpr(SuperExpr),
+    TernaryExpr(TernaryExpr),
+    FunctionExpr(FunctionExpr),
+    ListExpr(ListExpr),
+    IndexExpr(IndexExpr),
+    UpdateExpr(UpdateExpr),
+}
+
+// Identifies a syntax tree node by its address. Children always sit behind a
+// `Box`, `Vec` or `Rc`, so a node stays put while its tree is resolved and
+// run even if the tree itself is moved.
+pub fn node_id<T>(node: &T) -> usize {
+    node as *const T as usize
+}
+
+// Every expression can name the source line it came from. Most variants
+// already hold the token that best identifies them; the rest record a line.
+impl Expr {
+    pub fn line(&self) -> i32 {
+        match self {
+            Expr::LiteralExpr(expr) => expr.0.line,
+            Expr::UnaryExpr(expr) => expr.op.line,
+            Expr::BinaryExpr(expr) => expr.op.line,
+            Expr::GroupingExpr(expr) => expr.line,
+            Expr::VariableExpr(expr) => expr.0.line,
+            Expr::AssignExpr(expr) => expr.name.line,
+            Expr::LogicalExpr(expr) => expr.op.line,
+            Expr::CallExpr(expr) => expr.paren.line,
+            Expr::GetExpr(expr) => expr.name.line,
+            Expr::SetExpr(expr) => expr.name.line,
+            Expr::ThisExpr(expr) => expr.0.line,
+            Expr::SuperExpr(expr) => expr.method.line,
+            Expr::TernaryExpr(expr) => expr.line,
+            Expr::FunctionExpr(expr) => expr.0.name.line,
+            Expr::ListExpr(expr) => expr.bracket.line,
+            Expr::IndexExpr(expr) => expr.bracket.line,
+            Expr::UpdateExpr(expr) => expr.op.line,
+        }
+    }
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Serialize)]
 pub struct LiteralExpr(pub Token);
 
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Serialize)]
 pub struct UnaryExpr {
     pub op: Token,
     pub expr: Box<Expr>,
 }
-#[derive(PartialEq, Debug)]
+#[derive(PartialEq, Debug, Serialize)]
 pub struct BinaryExpr {
     pub left: Box<Expr>,
     pub op: Token,
     pub right: Box<Expr>,
 }
-#[derive(PartialEq, Debug)]
-pub struct GroupingExpr(pub Box<Expr>);
+#[derive(PartialEq, Debug, Serialize)]
+pub struct GroupingExpr {
+    pub expr: Box<Expr>,
+    // The line of the opening parenthesis.
+    pub line: i32,
+}
+
+#[derive(PartialEq, Debug, Serialize)]
+pub struct VariableExpr(pub Token);
+
+#[derive(PartialEq, Debug, Serialize)]
+pub struct AssignExpr {
+    pub name: Token,
+    pub value: Box<Expr>,
+}
+
+#[derive(PartialEq, Debug, Serialize)]
+pub struct LogicalExpr {
+    pub left: Box<Expr>,
+    pub op: Token,
+    pub right: Box<Expr>,
+}
+
+#[derive(PartialEq, Debug, Serialize)]
+pub struct CallExpr {
+    pub callee: Box<Expr>,
+    // The closing parenthesis, kept for reporting errors at the call site.
+    pub paren: Token,
+    pub args: Vec<Expr>,
+}
+
+#[derive(PartialEq, Debug, Serialize)]
+pub struct GetExpr {
+    pub object: Box<Expr>,
+    pub name: Token,
+}
+
+#[derive(PartialEq, Debug, Serialize)]
+pub struct SetExpr {
+    pub object: Box<Expr>,
+    pub name: Token,
+    pub value: Box<Expr>,
+}
+
+#[derive(PartialEq, Debug, Serialize)]
+pub struct ThisExpr(pub Token);
+
+#[derive(PartialEq, Debug, Serialize)]
+pub struct SuperExpr {
+    pub keyword: Token,
+    pub method: Token,
+}
+
+#[derive(PartialEq, Debug, Serialize)]
+pub struct TernaryExpr {
+    pub condition: Box<Expr>,
+    pub then_expr: Box<Expr>,
+    pub else_expr: Box<Expr>,
+    // The line of the `?`.
+    pub line: i32,
+}
+
+// An anonymous function such as `fun (x) { return x + 1; }`. It shares the
+// declaration's representation so calls go through `LoxFunction`; the name
+// is the `fun` keyword's position with an empty lexeme.
+#[derive(PartialEq, Debug, Serialize)]
+pub struct FunctionExpr(pub Rc<FunctionStmt>);
+
+#[derive(PartialEq, Debug, Serialize)]
+pub struct ListExpr {
+    // The opening bracket.
+    pub bracket: Token,
+    pub elements: Vec<Expr>,
+}
+
+// `++i`, `i++`, `--i` or `i--`. The prefix forms evaluate to the updated
+// value and the postfix forms to the value before the update.
+#[derive(PartialEq, Debug, Serialize)]
+pub struct UpdateExpr {
+    pub name: Token,
+    // Either `++` or `--`.
+    pub op: Token,
+    pub prefix: bool,
+}
+
+#[derive(PartialEq, Debug, Serialize)]
+pub struct IndexExpr {
+    pub object: Box<Expr>,
+    // The closing bracket, kept for reporting errors at the index.
+    pub bracket: Token,
+    pub index: Box<Expr>,
+}
+
+#[derive(PartialEq, Debug, Serialize)]
+#[serde(tag = "type", content = "node")]
+pub enum Stmt {
+    ExpressionStmt(ExpressionStmt),
+    PrintStmt(PrintStmt),
+    VarStmt(VarStmt),
+    BlockStmt(BlockStmt),
+    IfStmt(IfStmt),
+    WhileStmt(WhileStmt),
+    FunctionStmt(Rc<FunctionStmt>),
+    ReturnStmt(ReturnStmt),
+    BreakStmt(BreakStmt),
+    ContinueStmt(ContinueStmt),
+    ClassStmt(ClassStmt),
+    SwitchStmt(SwitchStmt),
+}
+
+#[derive(PartialEq, Debug, Serialize)]
+pub struct ExpressionStmt(pub Expr);
+
+#[derive(PartialEq, Debug, Serialize)]
+pub struct PrintStmt(pub Expr);
+
+#[derive(PartialEq, Debug, Serialize)]
+pub struct VarStmt {
+    pub name: Token,
+    pub initializer: Option<Expr>,
+}
+
+#[derive(PartialEq, Debug, Serialize)]
+pub struct BlockStmt(pub Vec<Stmt>);
+
+#[derive(PartialEq, Debug, Serialize)]
+pub struct IfStmt {
+    pub condition: Expr,
+    pub then_branch: Box<Stmt>,
+    pub else_branch: Option<Box<Stmt>>,
+}
+
+#[derive(PartialEq, Debug, Serialize)]
+pub struct WhileStmt {
+    pub condition: Expr,
+    pub body: Box<Stmt>,
+    // A desugared `for` loop's increment, run after the body even when it
+    // `continue`s.
+    pub increment: Option<Expr>,
+}
+
+#[derive(PartialEq, Debug, Serialize)]
+pub struct FunctionStmt {
+    pub name: Token,
+    pub params: Vec<Token>,
+    pub body: Vec<Stmt>,
+    // A method declared without a parameter list, which runs as soon as it's
+    // accessed instead of being called.
+    pub getter: bool,
+}
+
+#[derive(PartialEq, Debug, Serialize)]
+pub struct ReturnStmt {
+    pub keyword: Token,
+    pub value: Option<Expr>,
+}
+
+#[derive(PartialEq, Debug, Serialize)]
+pub struct BreakStmt(pub Token);
+
+#[derive(PartialEq, Debug, Serialize)]
+pub struct ContinueStmt(pub Token);
+
+#[derive(PartialEq, Debug, Serialize)]
+pub struct ClassStmt {
+    pub name: Token,
+    pub superclass: Option<VariableExpr>,
+    pub methods: Vec<Rc<FunctionStmt>>,
+    // Declared with a leading `class` and called on the class itself.
+    pub class_methods: Vec<Rc<FunctionStmt>>,
+}
+
+// Runs the first case whose value equals the discriminant, or the default
+// when none does. Cases don't fall through to the next one.
+#[derive(PartialEq, Debug, Serialize)]
+pub struct SwitchStmt {
+    pub discriminant: Expr,
+    pub cases: Vec<SwitchCase>,
+    pub default: Option<Vec<Stmt>>,
+}
+
+#[derive(PartialEq, Debug, Serialize)]
+pub struct SwitchCase {
+    pub value: Expr,
+    pub body: Vec<Stmt>,
+}
 
 #[derive(PartialEq, Debug, Clone)]
 pub enum Literal {
-    Number(f32),
+    Number(f64),
     String(String),
     Boolean(bool),
+    Callable(Rc<dyn LoxCallable>),
+    Class(Rc<LoxClass>),
+    Instance(Rc<RefCell<LoxInstance>>),
+    // Lists are immutable, so copies can share their elements.
+    List(Rc<Vec<Literal>>),
     Nil,
 }
 
+// Lox has a single number type, but some operations only make sense on whole
+// numbers. A number counts as an integer when it is finite, has no fractional
+// part and fits in an `i64`; anything else, including every non-number, has
+// no integer value. There is no rounding: `3.5` is simply not an integer.
+impl Literal {
+    pub fn is_integer(&self) -> bool {
+        self.as_i64().is_some()
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            // 2^63 itself is exactly representable but already out of range.
+            Self::Number(n) if n.fract() == 0.0 && *n >= -(2f64.powi(63)) && *n < 2f64.powi(63) => {
+                Some(*n as i64)
+            }
+            _ => None,
+        }
+    }
+}
+
 impl Display for Literal {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::Number(n) => write!(f, "{}", n),
+            Self::Number(n) => fmt_number(*n, f),
             Self::String(s) => write!(f, "{}", s),
             Self::Boolean(b) => write!(f, "{}", b),
+            Self::Callable(c) => write!(f, "{}", c),
+            Self::Class(c) => write!(f, "{}", c),
+            Self::Instance(i) => write!(f, "{}", i.borrow()),
+            Self::List(elements) => {
+                write!(f, "[")?;
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    fmt_list_element(element, f)?;
+                }
+                write!(f, "]")
+            }
             Self::Nil => write!(f, "nil"),
         }
     }
 }
+
+// Values serialize as their JSON counterparts. Callables, classes and
+// instances have no JSON form, so they fall back to their display text.
+impl Serialize for Literal {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Number(n) => serializer.serialize_f64(*n),
+            Self::String(s) => serializer.serialize_str(s),
+            Self::Boolean(b) => serializer.serialize_bool(*b),
+            Self::List(elements) => serializer.collect_seq(elements.iter()),
+            Self::Nil => serializer.serialize_unit(),
+            other => serializer.collect_str(other),
+        }
+    }
+}
+
+// Strings inside a list are quoted so `["a, b"]` can't be mistaken for
+// `["a", "b"]`; a top-level string still prints bare.
+fn fmt_list_element(element: &Literal, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match element {
+        Literal::String(s) => {
+            write!(f, "\"")?;
+            for c in s.chars() {
+                match c {
+                    '"' | '\\' => write!(f, "\\{}", c)?,
+                    _ => write!(f, "{}", c)?,
+                }
+            }
+            write!(f, "\"")
+        }
+        other => write!(f, "{}", other),
+    }
+}
+
+// Rust already prints whole numbers without a trailing ".0"; magnitudes too
+// large to spell out digit by digit switch to scientific notation. The
+// non-finite values print as `nan`, `inf` and `-inf`, and negative zero prints
+// as `0` since it compares equal to zero anyway.
+fn fmt_number(n: f64, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    if n == 0.0 {
+        write!(f, "0")
+    } else if n.is_nan() {
+        write!(f, "nan")
+    } else if n.is_infinite() {
+        write!(f, "{}", if n > 0.0 { "inf" } else { "-inf" })
+    } else if n.abs() >= 1e21 {
+        write!(f, "{:e}", n)
+    } else {
+        write!(f, "{}", n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error_reporter::ErrorReporter, parser::Parser, scanner::Scanner};
+
+    #[test]
+    fn display_whole_number() {
+        assert_eq!(Literal::Number(1.0).to_string(), "1");
+        assert_eq!(Literal::Number(-42.0).to_string(), "-42");
+    }
+
+    #[test]
+    fn display_fractional_number() {
+        assert_eq!(Literal::Number(1.5).to_string(), "1.5");
+        assert_eq!(Literal::Number(-0.25).to_string(), "-0.25");
+    }
+
+    #[test]
+    fn display_negative_zero() {
+        assert_eq!(Literal::Number(0.0).to_string(), "0");
+        assert_eq!(Literal::Number(-0.0).to_string(), "0");
+    }
+
+    #[test]
+    fn serialize_binary_expr() {
+        let mut error_reporter = ErrorReporter::new();
+        let tokens = Scanner::new("1 + 2".to_string(), &mut error_reporter).scan_tokens();
+        let expr = Parser::new(&tokens).parse().unwrap();
+
+        assert_eq!(
+            serde_json::to_string(&expr).unwrap(),
+            concat!(
+                r#"{"type":"BinaryExpr","node":{"#,
+                r#""left":{"type":"LiteralExpr","node":{"typ":{"Number":1.0},"lexeme":"1","line":1,"column":1,"visual_column":1,"start":0,"end":1}},"#,
+                r#""op":{"typ":"Plus","lexeme":"+","line":1,"column":3,"visual_column":3,"start":2,"end":3},"#,
+                r#""right":{"type":"LiteralExpr","node":{"typ":{"Number":2.0},"lexeme":"2","line":1,"column":5,"visual_column":5,"start":4,"end":5}}"#,
+                "}}"
+            )
+        );
+    }
+
+    #[test]
+    fn serialize_literal_values() {
+        assert_eq!(serde_json::to_string(&Literal::Number(1.5)).unwrap(), "1.5");
+        assert_eq!(serde_json::to_string(&Literal::Nil).unwrap(), "null");
+        assert_eq!(
+            serde_json::to_string(&Literal::String("hi".to_string())).unwrap(),
+            "\"hi\""
+        );
+    }
+
+    #[test]
+    fn integer_numbers() {
+        assert!(Literal::Number(3.0).is_integer());
+        assert!(!Literal::Number(3.5).is_integer());
+        assert_eq!(Literal::Number(-3.0).as_i64(), Some(-3));
+        assert_eq!(Literal::Number(3.5).as_i64(), None);
+    }
+
+    #[test]
+    fn non_integer_values() {
+        assert!(!Literal::Number(f64::NAN).is_integer());
+        assert!(!Literal::Number(f64::INFINITY).is_integer());
+        assert!(!Literal::Number(1e19).is_integer());
+        assert!(!Literal::String("3".to_string()).is_integer());
+    }
+
+    #[test]
+    fn display_list() {
+        let list = Literal::List(Rc::new(vec![
+            Literal::Number(1.0),
+            Literal::Number(2.0),
+            Literal::Number(3.0),
+        ]));
+
+        assert_eq!(list.to_string(), "[1, 2, 3]");
+        assert_eq!(Literal::List(Rc::new(vec![])).to_string(), "[]");
+    }
+
+    #[test]
+    fn display_list_quotes_strings() {
+        let list = Literal::List(Rc::new(vec![
+            Literal::Number(1.0),
+            Literal::String("a".to_string()),
+            Literal::Nil,
+            Literal::List(Rc::new(vec![Literal::String("b".to_string())])),
+        ]));
+
+        assert_eq!(list.to_string(), r#"[1, "a", nil, ["b"]]"#);
+        assert_eq!(Literal::String("a".to_string()).to_string(), "a");
+    }
+
+    #[test]
+    fn display_list_escapes_strings() {
+        let list = Literal::List(Rc::new(vec![Literal::String(
+            r#"say "hi" \ bye"#.to_string(),
+        )]));
+
+        assert_eq!(list.to_string(), r#"["say \"hi\" \\ bye"]"#);
+    }
+
+    #[test]
+    fn display_non_finite() {
+        assert_eq!(Literal::Number(f64::NAN).to_string(), "nan");
+        assert_eq!(Literal::Number(f64::INFINITY).to_string(), "inf");
+        assert_eq!(Literal::Number(f64::NEG_INFINITY).to_string(), "-inf");
+    }
+
+    #[test]
+    fn display_large_magnitude() {
+        assert_eq!(Literal::Number(1e21).to_string(), "1e21");
+        assert_eq!(Literal::Number(-2.5e300).to_string(), "-2.5e300");
+        assert_eq!(
+            Literal::Number(123456789012345680000.0).to_string(),
+            "123456789012345680000"
+        );
+    }
+}
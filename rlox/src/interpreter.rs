@@ -1,14 +1,227 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    io::{self, BufRead, BufReader, Write},
+    rc::Rc,
+};
+
 use crate::{
-    error_reporter::RuntimeError,
-    grammar::{BinaryExpr, Expr, GroupingExpr, Literal, LiteralExpr, UnaryExpr},
-    token::TokenType,
-    visitor::Visitor,
+    callable::{LoxCallable, LoxFunction},
+    class::{LoxClass, LoxInstance},
+    environment::Environment,
+    error_reporter::{RuntimeError, RuntimeErrorKind},
+    grammar::{
+        node_id, AssignExpr, BinaryExpr, BlockStmt, BreakStmt, CallExpr, ClassStmt, ContinueStmt,
+        Expr, ExpressionStmt, FunctionExpr, FunctionStmt, GetExpr, GroupingExpr, IfStmt, IndexExpr,
+        ListExpr, Literal, LiteralExpr, LogicalExpr, PrintStmt, ReturnStmt, SetExpr, Stmt,
+        SuperExpr, SwitchStmt, TernaryExpr, ThisExpr, UnaryExpr, UpdateExpr, VarStmt, VariableExpr,
+        WhileStmt,
+    },
+    natives::define_natives,
+    token::{Token, TokenType},
+    visitor::{StmtVisitor, Visitor},
 };
 
-pub struct Interpreter;
+// How a statement finished executing. `return`, `break` and `continue`
+// unwind through nested blocks as a value instead of a Rust panic or error.
+pub enum ControlFlow {
+    Normal,
+    Return(Literal),
+    Break,
+    Continue,
+}
+
+// Every Lox call recurses through several Rust frames, so unbounded Lox
+// recursion would overflow the native stack long before it ran out of heap.
+// Reaching this depth takes more than a default 2 MiB thread stack in debug
+// builds; the binary runs the interpreter on a thread with a larger one.
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 1000;
+
+// Rust's formatter can't go past `u16::MAX` digits, and nothing sensible
+// needs anywhere near that many.
+pub const MAX_PRINT_PRECISION: usize = 100;
+
+pub struct Interpreter {
+    environment: Rc<RefCell<Environment>>,
+    globals: Rc<RefCell<Environment>>,
+    // How many scopes out each local variable use finds its variable, keyed
+    // by `node_id`. Uses missing from here are globals.
+    locals: HashMap<usize, usize>,
+    // Where `print` statements write to.
+    output: Box<dyn Write>,
+    // Where the `read_line` native reads from.
+    input: Box<dyn BufRead>,
+    // How many calls are in progress, and how many may be before the
+    // program is stopped with a stack overflow error.
+    call_depth: usize,
+    max_call_depth: usize,
+    // How many fractional digits `print` shows for numbers, or `None` to
+    // print them the same way `str` does.
+    print_precision: Option<usize>,
+}
+
+impl Interpreter {
+    pub fn new() -> Interpreter {
+        Self::with_output(Box::new(io::stdout()))
+    }
+
+    pub fn with_output(output: Box<dyn Write>) -> Interpreter {
+        // The outermost scope starts out holding the native functions.
+        let mut globals = Environment::new();
+        define_natives(&mut globals);
+
+        let globals = Rc::new(RefCell::new(globals));
+        Interpreter {
+            environment: Rc::clone(&globals),
+            globals,
+            locals: HashMap::new(),
+            output,
+            input: Box::new(BufReader::new(io::stdin())),
+            call_depth: 0,
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            print_precision: None,
+        }
+    }
+
+    pub fn set_max_call_depth(&mut self, max_call_depth: usize) {
+        self.max_call_depth = max_call_depth;
+    }
+
+    pub fn set_print_precision(
+        &mut self,
+        print_precision: Option<usize>,
+    ) -> Result<(), RuntimeErrorKind> {
+        if print_precision.is_some_and(|precision| precision > MAX_PRINT_PRECISION) {
+            return Err(RuntimeErrorKind::InvalidPrecision);
+        }
+        self.print_precision = print_precision;
+        Ok(())
+    }
+
+    pub fn set_input(&mut self, input: Box<dyn BufRead>) {
+        self.input = input;
+    }
+
+    // The next line of input without its line ending, or `None` at EOF.
+    pub fn read_line(&mut self) -> io::Result<Option<String>> {
+        let mut line = String::new();
+        if self.input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let trimmed = line.trim_end_matches(['\n', '\r']).len();
+        line.truncate(trimmed);
+
+        Ok(Some(line))
+    }
+
+    // Records where the resolver found the variable used by `node`: `depth`
+    // scopes out, or among the globals for `None`.
+    pub fn resolve(&mut self, node: usize, depth: Option<usize>) {
+        match depth {
+            Some(depth) => self.locals.insert(node, depth),
+            // Node addresses can be reused once an old tree is dropped, so
+            // forget anything recorded for an earlier node at this address.
+            None => self.locals.remove(&node),
+        };
+    }
+
+    fn look_up_variable(&self, node: usize, name: &Token) -> Result<Literal, RuntimeError> {
+        match self.locals.get(&node) {
+            Some(&distance) => self.environment.borrow().get_at(distance, name),
+            None => self.globals.borrow().get(name),
+        }
+    }
+
+    fn assign_variable(
+        &mut self,
+        node: usize,
+        name: &Token,
+        value: Literal,
+    ) -> Result<(), RuntimeError> {
+        match self.locals.get(&node) {
+            Some(&distance) => self
+                .environment
+                .borrow_mut()
+                .assign_at(distance, name, value),
+            None => self.globals.borrow_mut().assign(name, value),
+        }
+    }
+
+    pub fn evaluate(&mut self, expr: &Expr) -> Result<Literal, RuntimeError> {
+        self.visit_expr(expr)
+    }
+
+    pub fn interpret(&mut self, statements: &[Stmt]) -> Result<(), RuntimeError> {
+        for stmt in statements {
+            if let ControlFlow::Return(_) = self.execute(stmt)? {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn execute(&mut self, stmt: &Stmt) -> Result<ControlFlow, RuntimeError> {
+        self.visit_stmt(stmt)
+    }
+
+    pub fn execute_block(
+        &mut self,
+        statements: &[Stmt],
+        environment: Rc<RefCell<Environment>>,
+    ) -> Result<ControlFlow, RuntimeError> {
+        let previous = std::mem::replace(&mut self.environment, environment);
+
+        let mut result = Ok(ControlFlow::Normal);
+        for stmt in statements {
+            result = self.execute(stmt);
+            if !matches!(result, Ok(ControlFlow::Normal)) {
+                break;
+            }
+        }
+
+        // Restore the outer scope even when a statement fails.
+        self.environment = previous;
+
+        result
+    }
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Lox follows Ruby's rule: nil and false are falsey, everything else is truthy.
+pub fn is_truthy(literal: &Literal) -> bool {
+    !matches!(literal, Literal::Nil | Literal::Boolean(false))
+}
+
+// Nil only equals nil, numbers, strings and booleans compare by value, and
+// functions, classes and instances are equal only to themselves. Values of
+// different types are never equal, so `1 == "1"` is false.
+pub fn is_equal(a: &Literal, b: &Literal) -> bool {
+    use Literal::*;
+
+    match (a, b) {
+        (Nil, Nil) => true,
+        (Number(a), Number(b)) => a == b,
+        (String(a), String(b)) => a == b,
+        (Boolean(a), Boolean(b)) => a == b,
+        (Callable(a), Callable(b)) => Rc::ptr_eq(a, b),
+        (Class(a), Class(b)) => Rc::ptr_eq(a, b),
+        (Instance(a), Instance(b)) => Rc::ptr_eq(a, b),
+        // Lists are values, so they compare element by element.
+        (List(a), List(b)) => {
+            a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| is_equal(a, b))
+        }
+        _ => false,
+    }
+}
 
 impl Visitor<Result<Literal, RuntimeError>> for Interpreter {
-    fn visit_expr(&self, expr: &Expr) -> Result<Literal, RuntimeError> {
+    fn visit_expr(&mut self, expr: &Expr) -> Result<Literal, RuntimeError> {
         use Expr::*;
 
         match expr {
@@ -16,10 +229,23 @@ impl Visitor<Result<Literal, RuntimeError>> for Interpreter {
             UnaryExpr(uexpr) => self.visit_unary_expr(uexpr),
             BinaryExpr(bexpr) => self.visit_binary_expr(bexpr),
             GroupingExpr(gexpr) => self.visit_grouping_expr(gexpr),
+            VariableExpr(vexpr) => self.visit_variable_expr(vexpr),
+            AssignExpr(aexpr) => self.visit_assign_expr(aexpr),
+            LogicalExpr(lexpr) => self.visit_logical_expr(lexpr),
+            CallExpr(cexpr) => self.visit_call_expr(cexpr),
+            GetExpr(gexpr) => self.visit_get_expr(gexpr),
+            SetExpr(sexpr) => self.visit_set_expr(sexpr),
+            ThisExpr(texpr) => self.visit_this_expr(texpr),
+            SuperExpr(sexpr) => self.visit_super_expr(sexpr),
+            TernaryExpr(texpr) => self.visit_ternary_expr(texpr),
+            FunctionExpr(fexpr) => self.visit_function_expr(fexpr),
+            ListExpr(lexpr) => self.visit_list_expr(lexpr),
+            IndexExpr(iexpr) => self.visit_index_expr(iexpr),
+            UpdateExpr(uexpr) => self.visit_update_expr(uexpr),
         }
     }
 
-    fn visit_literal_expr(&self, expr: &LiteralExpr) -> Result<Literal, RuntimeError> {
+    fn visit_literal_expr(&mut self, expr: &LiteralExpr) -> Result<Literal, RuntimeError> {
         let literal = match &expr.0.typ {
             TokenType::String(s) => Literal::String(s.clone()),
             TokenType::Number(n) => Literal::Number(*n),
@@ -32,37 +258,22 @@ impl Visitor<Result<Literal, RuntimeError>> for Interpreter {
         Ok(literal)
     }
 
-    fn visit_unary_expr(&self, unary_expr: &UnaryExpr) -> Result<Literal, RuntimeError> {
+    fn visit_unary_expr(&mut self, unary_expr: &UnaryExpr) -> Result<Literal, RuntimeError> {
         use Literal::*;
 
         let literal = self.visit_expr(&unary_expr.expr)?;
         let literal = match unary_expr.op.typ {
-            TokenType::Bang => match literal {
-                Boolean(b) => Boolean(!b),
-                a => {
-                    let message = format!(
-                        "Cannot perform '{:?}' on operand '{:?}'",
-                        unary_expr.op.typ, a
-                    );
-                    return Err(RuntimeError {
-                        line: unary_expr.op.line,
-                        message,
-                        exit_code: exitcode::DATAERR,
-                    });
-                }
-            },
+            TokenType::Bang => Boolean(!is_truthy(&literal)),
             TokenType::Minus => match literal {
                 Number(n) => Number(-n),
-                a => {
-                    let message = format!(
-                        "Cannot perform '{:?}' on operand '{:?}'",
-                        unary_expr.op.typ, a
-                    );
-                    return Err(RuntimeError {
-                        line: unary_expr.op.line,
-                        message,
-                        exit_code: exitcode::DATAERR,
-                    });
+                operand => {
+                    return Err(RuntimeError::new(
+                        unary_expr.op.line,
+                        RuntimeErrorKind::InvalidOperand {
+                            op: unary_expr.op.typ.clone(),
+                            operand: format!("{:?}", operand),
+                        },
+                    ));
                 }
             },
             _ => unreachable!(),
@@ -71,7 +282,7 @@ impl Visitor<Result<Literal, RuntimeError>> for Interpreter {
         Ok(literal)
     }
 
-    fn visit_binary_expr(&self, expr: &BinaryExpr) -> Result<Literal, RuntimeError> {
+    fn visit_binary_expr(&mut self, expr: &BinaryExpr) -> Result<Literal, RuntimeError> {
         use Literal::*;
 
         let left = self.visit_expr(&expr.left)?;
@@ -86,20 +297,35 @@ impl Visitor<Result<Literal, RuntimeError>> for Interpreter {
                 a.push_str(&b);
                 String(a)
             }
+            // When only one side is a string, the other is concatenated in
+            // the form `print` would show it, so `"count: " + 5` works.
+            (String(a), b, TokenType::Plus) => String(format!("{}{}", a, b)),
+            (a, String(b), TokenType::Plus) => String(format!("{}{}", a, b)),
             // Minus OP
             (Number(a), Number(b), TokenType::Minus) => Number(a - b),
             // Multiply OP
             (Number(a), Number(b), TokenType::Star) => Number(a * b),
             // Divide OP
+            (
+                Number(_),
+                Number(0.0),
+                TokenType::Slash | TokenType::Percent | TokenType::TildeSlash,
+            ) => {
+                return Err(RuntimeError::new(op.line, RuntimeErrorKind::DivisionByZero));
+            }
             (Number(a), Number(b), TokenType::Slash) => Number(a / b),
+            // Modulo OP. Truncating like C's fmod, so the result takes the
+            // sign of the dividend: -7 % 3 is -1.
+            (Number(a), Number(b), TokenType::Percent) => Number(a % b),
+            // Floor Divide OP. Rounds toward negative infinity, so -7 ~/ 2
+            // is -4, and always yields a whole number.
+            (Number(a), Number(b), TokenType::TildeSlash) => Number((a / b).floor()),
+            // Power OP
+            (Number(a), Number(b), TokenType::StarStar) => Number(a.powf(b)),
             // Equal OP
-            (Boolean(a), Boolean(b), TokenType::EqualEqual) => Boolean(a == b),
-            (Number(a), Number(b), TokenType::EqualEqual) => Boolean(a == b),
-            (String(a), String(b), TokenType::EqualEqual) => Boolean(a.eq(&b)),
+            (a, b, TokenType::EqualEqual) => Boolean(is_equal(&a, &b)),
             // Not Equal OP
-            (Boolean(a), Boolean(b), TokenType::BangEqual) => Boolean(a != b),
-            (Number(a), Number(b), TokenType::BangEqual) => Boolean(a != b),
-            (String(a), String(b), TokenType::BangEqual) => Boolean(!a.eq(&b)),
+            (a, b, TokenType::BangEqual) => Boolean(!is_equal(&a, &b)),
             // Less Than OP
             (Number(a), Number(b), TokenType::Less) => Boolean(a < b),
             // Less Than Or Equal OP
@@ -108,23 +334,1864 @@ impl Visitor<Result<Literal, RuntimeError>> for Interpreter {
             (Number(a), Number(b), TokenType::Greater) => Boolean(a > b),
             // Greater Than Or Equal OP
             (Number(a), Number(b), TokenType::GreaterEqual) => Boolean(a >= b),
-            (a, b, op) => {
-                let message = format!(
-                    "Cannot perform '{:?}' on operands '{:?}' and '{:?}'",
-                    op, a, b
-                );
-                return Err(RuntimeError {
-                    line: expr.op.line,
-                    message,
-                    exit_code: exitcode::DATAERR,
-                });
+            // Strings compare lexicographically by code point, like `str`'s
+            // `Ord`, so "ab" < "abc" and "Z" < "a".
+            (String(a), String(b), TokenType::Less) => Boolean(a < b),
+            (String(a), String(b), TokenType::LessEqual) => Boolean(a <= b),
+            (String(a), String(b), TokenType::Greater) => Boolean(a > b),
+            (String(a), String(b), TokenType::GreaterEqual) => Boolean(a >= b),
+            // Comma OP. Both operands were evaluated left to right; keep the last.
+            (_, b, TokenType::Comma) => b,
+            (left, right, op) => {
+                return Err(RuntimeError::new(
+                    expr.op.line,
+                    RuntimeErrorKind::TypeMismatch {
+                        op: op.clone(),
+                        left: format!("{:?}", left),
+                        right: format!("{:?}", right),
+                    },
+                ));
             }
         };
 
         Ok(literal)
     }
 
-    fn visit_grouping_expr(&self, expr: &GroupingExpr) -> Result<Literal, RuntimeError> {
-        self.visit_expr(&expr.0)
+    fn visit_grouping_expr(&mut self, expr: &GroupingExpr) -> Result<Literal, RuntimeError> {
+        self.visit_expr(&expr.expr)
+    }
+
+    fn visit_variable_expr(&mut self, expr: &VariableExpr) -> Result<Literal, RuntimeError> {
+        self.look_up_variable(node_id(expr), &expr.0)
+    }
+
+    fn visit_assign_expr(&mut self, expr: &AssignExpr) -> Result<Literal, RuntimeError> {
+        let value = self.visit_expr(&expr.value)?;
+        self.assign_variable(node_id(expr), &expr.name, value.clone())?;
+
+        Ok(value)
+    }
+
+    fn visit_update_expr(&mut self, expr: &UpdateExpr) -> Result<Literal, RuntimeError> {
+        let Literal::Number(old) = self.look_up_variable(node_id(expr), &expr.name)? else {
+            return Err(RuntimeError::new(
+                expr.op.line,
+                RuntimeErrorKind::InvalidUpdate,
+            ));
+        };
+        let new = match expr.op.typ {
+            TokenType::PlusPlus => old + 1.0,
+            TokenType::MinusMinus => old - 1.0,
+            _ => unreachable!(),
+        };
+        self.assign_variable(node_id(expr), &expr.name, Literal::Number(new))?;
+
+        Ok(Literal::Number(if expr.prefix { new } else { old }))
+    }
+
+    // Logical operators yield one of their operands rather than a coerced
+    // boolean, and only evaluate the right operand when they have to.
+    fn visit_logical_expr(&mut self, expr: &LogicalExpr) -> Result<Literal, RuntimeError> {
+        let left = self.visit_expr(&expr.left)?;
+
+        let short_circuits = match expr.op.typ {
+            TokenType::Or => is_truthy(&left),
+            TokenType::And => !is_truthy(&left),
+            // Unlike `or`, only `nil` falls through to the right operand.
+            TokenType::QuestionQuestion => !matches!(left, Literal::Nil),
+            _ => unreachable!(),
+        };
+        if short_circuits {
+            return Ok(left);
+        }
+
+        self.visit_expr(&expr.right)
+    }
+
+    fn visit_ternary_expr(&mut self, expr: &TernaryExpr) -> Result<Literal, RuntimeError> {
+        let condition = self.visit_expr(&expr.condition)?;
+
+        if is_truthy(&condition) {
+            self.visit_expr(&expr.then_expr)
+        } else {
+            self.visit_expr(&expr.else_expr)
+        }
+    }
+
+    fn visit_function_expr(&mut self, expr: &FunctionExpr) -> Result<Literal, RuntimeError> {
+        let function = LoxFunction::new(Rc::clone(&expr.0), Rc::clone(&self.environment), false);
+
+        Ok(Literal::Callable(Rc::new(function)))
+    }
+
+    fn visit_list_expr(&mut self, expr: &ListExpr) -> Result<Literal, RuntimeError> {
+        let mut elements = Vec::with_capacity(expr.elements.len());
+        for element in &expr.elements {
+            elements.push(self.visit_expr(element)?);
+        }
+
+        Ok(Literal::List(Rc::new(elements)))
+    }
+
+    fn visit_index_expr(&mut self, expr: &IndexExpr) -> Result<Literal, RuntimeError> {
+        let object = self.visit_expr(&expr.object)?;
+        let index = self.visit_expr(&expr.index)?;
+        let error = |kind| RuntimeError::new(expr.bracket.line, kind);
+
+        let Literal::List(elements) = object else {
+            return Err(error(RuntimeErrorKind::NotAList));
+        };
+        let Some(index) = index.as_i64() else {
+            return Err(error(RuntimeErrorKind::NonIntegerIndex));
+        };
+        usize::try_from(index)
+            .ok()
+            .and_then(|index| elements.get(index))
+            .cloned()
+            .ok_or_else(|| error(RuntimeErrorKind::IndexOutOfRange))
+    }
+
+    fn visit_call_expr(&mut self, expr: &CallExpr) -> Result<Literal, RuntimeError> {
+        let callee = self.visit_expr(&expr.callee)?;
+
+        let mut arguments = Vec::with_capacity(expr.args.len());
+        for arg in &expr.args {
+            arguments.push(self.visit_expr(arg)?);
+        }
+
+        let callable: Rc<dyn LoxCallable> = match callee {
+            Literal::Callable(callable) => callable,
+            Literal::Class(class) => class,
+            // Blame the callee rather than the closing parenthesis, which
+            // may sit several lines later.
+            _ => {
+                return Err(RuntimeError::new(
+                    expr.callee.line(),
+                    RuntimeErrorKind::NotCallable,
+                ))
+            }
+        };
+
+        let arity = callable.arity();
+        if !arity.accepts(arguments.len()) {
+            return Err(RuntimeError::new(
+                expr.paren.line,
+                RuntimeErrorKind::ArityMismatch {
+                    expected: arity,
+                    got: arguments.len(),
+                },
+            ));
+        }
+
+        if self.call_depth >= self.max_call_depth {
+            return Err(RuntimeError::new(
+                expr.paren.line,
+                RuntimeErrorKind::StackOverflow,
+            ));
+        }
+
+        self.call_depth += 1;
+        let result = callable.call(self, arguments, expr.paren.line);
+        self.call_depth -= 1;
+
+        result
+    }
+
+    fn visit_get_expr(&mut self, expr: &GetExpr) -> Result<Literal, RuntimeError> {
+        match self.visit_expr(&expr.object)? {
+            Literal::Instance(instance) => LoxInstance::get(&instance, &expr.name, self),
+            Literal::Class(class) => match class.find_class_method(&expr.name.lexeme) {
+                Some(method) => method.get_or_call(self, expr.name.line),
+                None => Err(RuntimeError::new(
+                    expr.name.line,
+                    RuntimeErrorKind::UndefinedProperty(expr.name.lexeme.clone()),
+                )),
+            },
+            _ => Err(RuntimeError::new(
+                expr.name.line,
+                RuntimeErrorKind::NoProperties,
+            )),
+        }
+    }
+
+    fn visit_set_expr(&mut self, expr: &SetExpr) -> Result<Literal, RuntimeError> {
+        let instance = match self.visit_expr(&expr.object)? {
+            Literal::Instance(instance) => instance,
+            _ => {
+                return Err(RuntimeError::new(
+                    expr.name.line,
+                    RuntimeErrorKind::NoFields,
+                ))
+            }
+        };
+
+        let value = self.visit_expr(&expr.value)?;
+        instance.borrow_mut().set(&expr.name, value.clone());
+
+        Ok(value)
+    }
+
+    fn visit_this_expr(&mut self, expr: &ThisExpr) -> Result<Literal, RuntimeError> {
+        self.look_up_variable(node_id(expr), &expr.0)
+    }
+
+    fn visit_super_expr(&mut self, expr: &SuperExpr) -> Result<Literal, RuntimeError> {
+        let distance = *self
+            .locals
+            .get(&node_id(expr))
+            .expect("'super' is always resolved to a local scope.");
+        let environment = self.environment.borrow();
+        let superclass = match environment.get_at(distance, &expr.keyword)? {
+            Literal::Class(class) => class,
+            _ => unreachable!(),
+        };
+        // `this` lives in the scope just inside the one holding `super`.
+        let this = Token::new(
+            TokenType::This,
+            "this".to_string(),
+            expr.keyword.line,
+            0,
+            0,
+            0,
+            0,
+        );
+        let instance = match environment.get_at(distance - 1, &this)? {
+            Literal::Instance(instance) => instance,
+            _ => unreachable!(),
+        };
+        drop(environment);
+
+        match superclass.find_method(&expr.method.lexeme) {
+            Some(method) => Rc::new(method.bind(instance)).get_or_call(self, expr.method.line),
+            None => Err(RuntimeError::new(
+                expr.method.line,
+                RuntimeErrorKind::UndefinedProperty(expr.method.lexeme.clone()),
+            )),
+        }
+    }
+}
+
+impl StmtVisitor<Result<ControlFlow, RuntimeError>> for Interpreter {
+    fn visit_stmt(&mut self, stmt: &Stmt) -> Result<ControlFlow, RuntimeError> {
+        match stmt {
+            Stmt::ExpressionStmt(stmt) => self.visit_expression_stmt(stmt),
+            Stmt::PrintStmt(stmt) => self.visit_print_stmt(stmt),
+            Stmt::VarStmt(stmt) => self.visit_var_stmt(stmt),
+            Stmt::BlockStmt(stmt) => self.visit_block_stmt(stmt),
+            Stmt::IfStmt(stmt) => self.visit_if_stmt(stmt),
+            Stmt::WhileStmt(stmt) => self.visit_while_stmt(stmt),
+            Stmt::FunctionStmt(stmt) => self.visit_function_stmt(stmt),
+            Stmt::ReturnStmt(stmt) => self.visit_return_stmt(stmt),
+            Stmt::BreakStmt(stmt) => self.visit_break_stmt(stmt),
+            Stmt::ContinueStmt(stmt) => self.visit_continue_stmt(stmt),
+            Stmt::ClassStmt(stmt) => self.visit_class_stmt(stmt),
+            Stmt::SwitchStmt(stmt) => self.visit_switch_stmt(stmt),
+        }
+    }
+
+    fn visit_expression_stmt(
+        &mut self,
+        stmt: &ExpressionStmt,
+    ) -> Result<ControlFlow, RuntimeError> {
+        self.visit_expr(&stmt.0)?;
+        Ok(ControlFlow::Normal)
+    }
+
+    fn visit_print_stmt(&mut self, stmt: &PrintStmt) -> Result<ControlFlow, RuntimeError> {
+        let literal = self.visit_expr(&stmt.0)?;
+        match (&literal, self.print_precision) {
+            // Adding zero turns -0 into 0, matching the default format.
+            (Literal::Number(n), Some(precision)) if n.is_finite() => {
+                writeln!(self.output, "{:.*}", precision, n + 0.0)
+            }
+            _ => writeln!(self.output, "{}", literal),
+        }
+        .expect("Failed to write print output.");
+        Ok(ControlFlow::Normal)
+    }
+
+    fn visit_var_stmt(&mut self, stmt: &VarStmt) -> Result<ControlFlow, RuntimeError> {
+        let value = match &stmt.initializer {
+            Some(expr) => self.visit_expr(expr)?,
+            None => Literal::Nil,
+        };
+        self.environment
+            .borrow_mut()
+            .define(stmt.name.lexeme.clone(), value);
+        Ok(ControlFlow::Normal)
+    }
+
+    fn visit_block_stmt(&mut self, stmt: &BlockStmt) -> Result<ControlFlow, RuntimeError> {
+        let environment = Environment::with_enclosing(Rc::clone(&self.environment));
+        self.execute_block(&stmt.0, Rc::new(RefCell::new(environment)))
+    }
+
+    fn visit_if_stmt(&mut self, stmt: &IfStmt) -> Result<ControlFlow, RuntimeError> {
+        if is_truthy(&self.visit_expr(&stmt.condition)?) {
+            self.execute(&stmt.then_branch)
+        } else if let Some(else_branch) = &stmt.else_branch {
+            self.execute(else_branch)
+        } else {
+            Ok(ControlFlow::Normal)
+        }
+    }
+
+    fn visit_while_stmt(&mut self, stmt: &WhileStmt) -> Result<ControlFlow, RuntimeError> {
+        while is_truthy(&self.visit_expr(&stmt.condition)?) {
+            match self.execute(&stmt.body)? {
+                ControlFlow::Normal | ControlFlow::Continue => (),
+                ControlFlow::Break => break,
+                flow @ ControlFlow::Return(_) => return Ok(flow),
+            }
+
+            if let Some(increment) = &stmt.increment {
+                self.visit_expr(increment)?;
+            }
+        }
+
+        Ok(ControlFlow::Normal)
+    }
+
+    fn visit_function_stmt(
+        &mut self,
+        stmt: &Rc<FunctionStmt>,
+    ) -> Result<ControlFlow, RuntimeError> {
+        let function = LoxFunction::new(Rc::clone(stmt), Rc::clone(&self.environment), false);
+        self.environment.borrow_mut().define(
+            stmt.name.lexeme.clone(),
+            Literal::Callable(Rc::new(function)),
+        );
+        Ok(ControlFlow::Normal)
+    }
+
+    fn visit_return_stmt(&mut self, stmt: &ReturnStmt) -> Result<ControlFlow, RuntimeError> {
+        let value = match &stmt.value {
+            Some(expr) => self.visit_expr(expr)?,
+            None => Literal::Nil,
+        };
+        Ok(ControlFlow::Return(value))
+    }
+
+    fn visit_break_stmt(&mut self, _: &BreakStmt) -> Result<ControlFlow, RuntimeError> {
+        Ok(ControlFlow::Break)
+    }
+
+    fn visit_continue_stmt(&mut self, _: &ContinueStmt) -> Result<ControlFlow, RuntimeError> {
+        Ok(ControlFlow::Continue)
+    }
+
+    fn visit_class_stmt(&mut self, stmt: &ClassStmt) -> Result<ControlFlow, RuntimeError> {
+        let ClassStmt {
+            name,
+            superclass,
+            methods,
+            class_methods,
+        } = stmt;
+        let superclass = match superclass {
+            Some(superclass) => match self.visit_variable_expr(superclass)? {
+                Literal::Class(class) => Some(class),
+                _ => {
+                    return Err(RuntimeError::new(
+                        superclass.0.line,
+                        RuntimeErrorKind::InvalidSuperclass,
+                    ))
+                }
+            },
+            None => None,
+        };
+
+        // Methods of a subclass close over a scope holding `super`.
+        let enclosing = Rc::clone(&self.environment);
+        if let Some(superclass) = &superclass {
+            let mut environment = Environment::with_enclosing(Rc::clone(&enclosing));
+            environment.define("super".to_string(), Literal::Class(Rc::clone(superclass)));
+            self.environment = Rc::new(RefCell::new(environment));
+        }
+
+        let methods = methods
+            .iter()
+            .map(|method| {
+                let function = LoxFunction::new(
+                    Rc::clone(method),
+                    Rc::clone(&self.environment),
+                    method.name.lexeme == "init",
+                );
+                (method.name.lexeme.clone(), Rc::new(function))
+            })
+            .collect();
+        let class_methods = class_methods
+            .iter()
+            .map(|method| {
+                let function =
+                    LoxFunction::new(Rc::clone(method), Rc::clone(&self.environment), false);
+                (method.name.lexeme.clone(), Rc::new(function))
+            })
+            .collect();
+
+        self.environment = enclosing;
+
+        let class = LoxClass::new(name.lexeme.clone(), superclass, methods, class_methods);
+        self.environment
+            .borrow_mut()
+            .define(name.lexeme.clone(), Literal::Class(Rc::new(class)));
+        Ok(ControlFlow::Normal)
+    }
+
+    fn visit_switch_stmt(&mut self, stmt: &SwitchStmt) -> Result<ControlFlow, RuntimeError> {
+        let value = self.visit_expr(&stmt.discriminant)?;
+
+        // Case values are evaluated in order and only until one matches.
+        let mut body = stmt.default.as_ref();
+        for case in &stmt.cases {
+            if is_equal(&value, &self.visit_expr(&case.value)?) {
+                body = Some(&case.body);
+                break;
+            }
+        }
+
+        match body {
+            Some(body) => {
+                let environment = Environment::with_enclosing(Rc::clone(&self.environment));
+                self.execute_block(body, Rc::new(RefCell::new(environment)))
+            }
+            None => Ok(ControlFlow::Normal),
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::*;
+    use crate::{
+        callable::Arity, error_reporter::ErrorReporter, parser::Parser, resolver::Resolver,
+        scanner::Scanner,
+    };
+
+    #[test]
+    fn large_integers_round_trip() {
+        let mut interpreter = Interpreter::new();
+        let literal = evaluate(&mut interpreter, "123456789012345").unwrap();
+
+        assert_eq!(literal, Literal::Number(123456789012345.0));
+        assert_eq!(literal.to_string(), "123456789012345");
+    }
+
+    #[test]
+    fn bang_negates_truthiness() {
+        let mut interpreter = Interpreter::new();
+
+        assert_eq!(
+            evaluate(&mut interpreter, "!nil"),
+            Ok(Literal::Boolean(true))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "!0"),
+            Ok(Literal::Boolean(false))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "!\"x\""),
+            Ok(Literal::Boolean(false))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "!!nil"),
+            Ok(Literal::Boolean(false))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "!true"),
+            Ok(Literal::Boolean(false))
+        );
+    }
+
+    #[test]
+    fn equality_across_types() {
+        let mut interpreter = Interpreter::new();
+
+        assert_eq!(
+            evaluate(&mut interpreter, "nil == nil"),
+            Ok(Literal::Boolean(true))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "1 == \"1\""),
+            Ok(Literal::Boolean(false))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "nil == false"),
+            Ok(Literal::Boolean(false))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "true != nil"),
+            Ok(Literal::Boolean(true))
+        );
+    }
+
+    #[test]
+    fn string_comparison() {
+        let mut interpreter = Interpreter::new();
+
+        for (source, expected) in [
+            ("\"apple\" < \"banana\"", true),
+            ("\"banana\" < \"apple\"", false),
+            ("\"ab\" < \"abc\"", true),
+            ("\"abc\" > \"ab\"", true),
+            ("\"ab\" <= \"ab\"", true),
+            ("\"ab\" >= \"abc\"", false),
+            ("\"Z\" < \"a\"", true),
+        ] {
+            assert_eq!(
+                evaluate(&mut interpreter, source),
+                Ok(Literal::Boolean(expected)),
+                "{}",
+                source
+            );
+        }
+    }
+
+    // Division by zero is an error, so NaN comes from overflowing to
+    // infinity instead. Equality follows IEEE 754: NaN equals nothing.
+    #[test]
+    fn nan_is_not_equal_to_itself() {
+        let (mut interpreter, output) = capturing_interpreter();
+        execute(
+            &mut interpreter,
+            "var big = 1e308 * 10;
+            var nan = big - big;
+            print big;
+            print nan;
+            print nan == nan;
+            print nan != nan;",
+        )
+        .unwrap();
+
+        assert_eq!(output.contents(), "inf\nnan\nfalse\ntrue\n");
+    }
+
+    #[test]
+    fn print_precision() {
+        let (mut interpreter, output) = capturing_interpreter();
+        execute(
+            &mut interpreter,
+            "print 1/3;
+            setprecision(4);
+            print 1/3;
+            print 2;
+            print \"1.5\";
+            setprecision(nil);
+            print 2;",
+        )
+        .unwrap();
+
+        assert_eq!(
+            output.contents(),
+            "0.3333333333333333\n0.3333\n2.0000\n1.5\n2\n"
+        );
+    }
+
+    #[test]
+    fn print_precision_set_by_embedder() {
+        let (mut interpreter, output) = capturing_interpreter();
+        interpreter.set_print_precision(Some(2)).unwrap();
+        execute(&mut interpreter, "print -0; print 2/3;").unwrap();
+
+        assert_eq!(output.contents(), "0.00\n0.67\n");
+    }
+
+    #[test]
+    fn setprecision_rejects_invalid_precision() {
+        let mut interpreter = Interpreter::new();
+
+        for source in [
+            "setprecision(-1)",
+            "setprecision(1.5)",
+            "setprecision(\"2\")",
+            "setprecision(101)",
+            "setprecision(70000)",
+        ] {
+            assert_eq!(
+                evaluate(&mut interpreter, source)
+                    .unwrap_err()
+                    .kind
+                    .to_string(),
+                "Precision must be an integer from 0 to 100 or nil."
+            );
+        }
+        assert_eq!(
+            interpreter.set_print_precision(Some(101)),
+            Err(RuntimeErrorKind::InvalidPrecision)
+        );
+    }
+
+    #[test]
+    fn comparison_across_types_errors() {
+        let mut interpreter = Interpreter::new();
+
+        assert!(evaluate(&mut interpreter, "1 < \"2\"").is_err());
+        assert!(evaluate(&mut interpreter, "nil - 1").is_err());
+    }
+
+    #[test]
+    fn power() {
+        let mut interpreter = Interpreter::new();
+
+        assert_eq!(
+            evaluate(&mut interpreter, "2 ** 10"),
+            Ok(Literal::Number(1024.0))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "2 ** 3 ** 2"),
+            Ok(Literal::Number(512.0))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "-2 ** 2"),
+            Ok(Literal::Number(-4.0))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "2 ** -1"),
+            Ok(Literal::Number(0.5))
+        );
+    }
+
+    #[test]
+    fn plus_stringifies_non_string_operand() {
+        let mut interpreter = Interpreter::new();
+
+        assert_eq!(
+            evaluate(&mut interpreter, "\"x\" + 1"),
+            Ok(Literal::String("x1".to_string()))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "1 + \"x\""),
+            Ok(Literal::String("1x".to_string()))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "1 + 2"),
+            Ok(Literal::Number(3.0))
+        );
+    }
+
+    #[test]
+    fn division() {
+        let mut interpreter = Interpreter::new();
+
+        assert_eq!(
+            evaluate(&mut interpreter, "7 / 2"),
+            Ok(Literal::Number(3.5))
+        );
+    }
+
+    #[test]
+    fn division_by_zero() {
+        let mut interpreter = Interpreter::new();
+        let error = evaluate(&mut interpreter, "1 /\n 0").unwrap_err();
+
+        assert_eq!(error.line, 1);
+        assert_eq!(error.kind, RuntimeErrorKind::DivisionByZero);
+    }
+
+    #[test]
+    fn modulo() {
+        let mut interpreter = Interpreter::new();
+
+        assert_eq!(
+            evaluate(&mut interpreter, "7 % 3"),
+            Ok(Literal::Number(1.0))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "-7 % 3"),
+            Ok(Literal::Number(-1.0))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "7 % 0")
+                .unwrap_err()
+                .kind
+                .to_string(),
+            "Division by zero."
+        );
+    }
+
+    #[test]
+    fn increment_and_decrement() {
+        let (mut interpreter, output) = capturing_interpreter();
+        execute(
+            &mut interpreter,
+            "var i = 1;
+            print i++;
+            print i;
+            print ++i;
+            print i;
+            print i--;
+            print --i;
+            {
+                var j = 5;
+                fun bump() { j++; }
+                bump();
+                print j;
+            }",
+        )
+        .unwrap();
+
+        assert_eq!(output.contents(), "1\n2\n3\n3\n3\n1\n6\n");
+    }
+
+    #[test]
+    fn increment_rejects_non_numbers() {
+        let mut interpreter = Interpreter::new();
+        let error = execute(&mut interpreter, "var s = \"a\"; s++;").unwrap_err();
+
+        assert_eq!(
+            error.kind.to_string(),
+            "Can only increment or decrement a number."
+        );
+    }
+
+    #[test]
+    fn floor_division() {
+        let mut interpreter = Interpreter::new();
+
+        assert_eq!(
+            evaluate(&mut interpreter, "7 ~/ 2"),
+            Ok(Literal::Number(3.0))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "-7 ~/ 2"),
+            Ok(Literal::Number(-4.0))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "7.5 ~/ 2.5"),
+            Ok(Literal::Number(3.0))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "7 ~/ 0")
+                .unwrap_err()
+                .kind
+                .to_string(),
+            "Division by zero."
+        );
+    }
+
+    #[test]
+    fn define_and_read_variable() {
+        let mut interpreter = Interpreter::new();
+        execute(&mut interpreter, "var x = 1; var y;").unwrap();
+
+        assert_eq!(evaluate(&mut interpreter, "x"), Ok(Literal::Number(1.0)));
+        assert_eq!(evaluate(&mut interpreter, "y"), Ok(Literal::Nil));
+    }
+
+    #[test]
+    fn read_undefined_variable() {
+        let mut interpreter = Interpreter::new();
+        let error = evaluate(&mut interpreter, "\n\nundefined").unwrap_err();
+
+        assert_eq!(error.line, 3);
+        assert_eq!(error.kind.to_string(), "Undefined variable 'undefined'.");
+    }
+
+    #[test]
+    fn assign_variables() {
+        let mut interpreter = Interpreter::new();
+        execute(&mut interpreter, "var x; var y; x = y = 3;").unwrap();
+
+        assert_eq!(evaluate(&mut interpreter, "x"), Ok(Literal::Number(3.0)));
+        assert_eq!(evaluate(&mut interpreter, "y"), Ok(Literal::Number(3.0)));
+    }
+
+    #[test]
+    fn assign_undeclared_variable() {
+        let mut interpreter = Interpreter::new();
+        let error = execute(&mut interpreter, "x = 5;").unwrap_err();
+
+        assert_eq!(
+            error.kind,
+            RuntimeErrorKind::UndefinedVariable("x".to_string())
+        );
+    }
+
+    #[test]
+    fn block_shadows_outer_variable() {
+        let mut interpreter = Interpreter::new();
+        execute(
+            &mut interpreter,
+            "var x = \"outer\"; var y; { var x = \"inner\"; y = x; }",
+        )
+        .unwrap();
+
+        assert_eq!(
+            evaluate(&mut interpreter, "x"),
+            Ok(Literal::String("outer".to_string()))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "y"),
+            Ok(Literal::String("inner".to_string()))
+        );
+    }
+
+    #[test]
+    fn block_restores_scope_after_error() {
+        let mut interpreter = Interpreter::new();
+        execute(&mut interpreter, "var x = 1;").unwrap();
+        execute(&mut interpreter, "{ var x = 2; undefined; }").unwrap_err();
+
+        assert_eq!(evaluate(&mut interpreter, "x"), Ok(Literal::Number(1.0)));
+    }
+
+    #[test]
+    fn if_takes_then_branch() {
+        let mut interpreter = Interpreter::new();
+        execute(&mut interpreter, "var x; if (0) x = 1; else x = 2;").unwrap();
+
+        assert_eq!(evaluate(&mut interpreter, "x"), Ok(Literal::Number(1.0)));
+    }
+
+    #[test]
+    fn if_takes_else_branch() {
+        let mut interpreter = Interpreter::new();
+        execute(&mut interpreter, "var x; if (nil) x = 1; else x = 2;").unwrap();
+
+        assert_eq!(evaluate(&mut interpreter, "x"), Ok(Literal::Number(2.0)));
+    }
+
+    #[test]
+    fn if_skips_untaken_branch_without_else() {
+        let mut interpreter = Interpreter::new();
+        execute(&mut interpreter, "var x = 0; if (false) x = 1;").unwrap();
+
+        assert_eq!(evaluate(&mut interpreter, "x"), Ok(Literal::Number(0.0)));
+    }
+
+    #[test]
+    fn if_else_if_chain() {
+        let mut interpreter = Interpreter::new();
+        execute(
+            &mut interpreter,
+            "var x; if (false) x = 1; else if (true) x = 2; else x = 3;",
+        )
+        .unwrap();
+
+        assert_eq!(evaluate(&mut interpreter, "x"), Ok(Literal::Number(2.0)));
+    }
+
+    #[test]
+    fn logical_operators_return_operands() {
+        let mut interpreter = Interpreter::new();
+
+        assert_eq!(
+            evaluate(&mut interpreter, "nil or \"hi\""),
+            Ok(Literal::String("hi".to_string()))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "1 and 2"),
+            Ok(Literal::Number(2.0))
+        );
+        assert_eq!(evaluate(&mut interpreter, "nil and 2"), Ok(Literal::Nil));
+    }
+
+    #[test]
+    fn logical_operators_short_circuit() {
+        let mut interpreter = Interpreter::new();
+        execute(
+            &mut interpreter,
+            "var x = 0; true or (x = 1); false and (x = 2);",
+        )
+        .unwrap();
+
+        assert_eq!(evaluate(&mut interpreter, "x"), Ok(Literal::Number(0.0)));
+    }
+
+    #[test]
+    fn nil_coalescing_only_falls_through_on_nil() {
+        let mut interpreter = Interpreter::new();
+
+        assert_eq!(
+            evaluate(&mut interpreter, "nil ?? 5"),
+            Ok(Literal::Number(5.0))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "false ?? 5"),
+            Ok(Literal::Boolean(false))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "0 ?? 5"),
+            Ok(Literal::Number(0.0))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "nil ?? nil ?? 3"),
+            Ok(Literal::Number(3.0))
+        );
+    }
+
+    #[test]
+    fn nil_coalescing_short_circuits() {
+        let mut interpreter = Interpreter::new();
+        execute(
+            &mut interpreter,
+            "var x = 0; 1 ?? (x = 1); nil ?? (x = x + 2);",
+        )
+        .unwrap();
+
+        assert_eq!(evaluate(&mut interpreter, "x"), Ok(Literal::Number(2.0)));
+    }
+
+    #[test]
+    fn comma_yields_last_operand() {
+        let mut interpreter = Interpreter::new();
+
+        assert_eq!(
+            evaluate(&mut interpreter, "(1, 2, 3)"),
+            Ok(Literal::Number(3.0))
+        );
+    }
+
+    #[test]
+    fn comma_evaluates_every_operand() {
+        let (mut interpreter, output) = capturing_interpreter();
+        execute(
+            &mut interpreter,
+            "var x = 0; fun f(a, b) { print a + b; } f((x = 1, x + 1), 10);",
+        )
+        .unwrap();
+
+        assert_eq!(output.contents(), "12\n");
+    }
+
+    #[test]
+    fn ternary_picks_a_branch() {
+        let mut interpreter = Interpreter::new();
+
+        assert_eq!(
+            evaluate(&mut interpreter, "1 < 2 ? \"yes\" : \"no\""),
+            Ok(Literal::String("yes".to_string()))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "nil ? 1 : false ? 2 : 3"),
+            Ok(Literal::Number(3.0))
+        );
+    }
+
+    #[test]
+    fn ternary_evaluates_only_the_chosen_branch() {
+        let mut interpreter = Interpreter::new();
+        execute(
+            &mut interpreter,
+            "var x = 0; true ? x : (x = 1); false ? (x = 2) : x;",
+        )
+        .unwrap();
+
+        assert_eq!(evaluate(&mut interpreter, "x"), Ok(Literal::Number(0.0)));
+    }
+
+    #[test]
+    fn break_exits_loop() {
+        let (mut interpreter, output) = capturing_interpreter();
+        execute(
+            &mut interpreter,
+            "var i = 0; while (true) { if (i == 3) break; print i; i = i + 1; }",
+        )
+        .unwrap();
+
+        assert_eq!(output.contents(), "0\n1\n2\n");
+    }
+
+    #[test]
+    fn continue_runs_for_increment() {
+        let (mut interpreter, output) = capturing_interpreter();
+        execute(
+            &mut interpreter,
+            "for (var i = 0; i < 5; i = i + 1) { if (i % 2 == 0) continue; print i; }",
+        )
+        .unwrap();
+
+        assert_eq!(output.contents(), "1\n3\n");
+    }
+
+    #[test]
+    fn break_leaves_only_innermost_loop() {
+        let (mut interpreter, output) = capturing_interpreter();
+        execute(
+            &mut interpreter,
+            "for (var i = 0; i < 2; i = i + 1) { while (true) break; print i; }",
+        )
+        .unwrap();
+
+        assert_eq!(output.contents(), "0\n1\n");
+    }
+
+    #[test]
+    fn call_anonymous_function() {
+        let (mut interpreter, output) = capturing_interpreter();
+        execute(
+            &mut interpreter,
+            "var f = fun (x) { return x + 1; }; print f(1); print f;",
+        )
+        .unwrap();
+
+        assert_eq!(output.contents(), "2\n<fn>\n");
+    }
+
+    #[test]
+    fn anonymous_function_captures_scope() {
+        let mut interpreter = Interpreter::new();
+        execute(
+            &mut interpreter,
+            "fun adder(n) { return fun (x) { return x + n; }; } var add2 = adder(2);",
+        )
+        .unwrap();
+
+        assert_eq!(
+            evaluate(&mut interpreter, "add2(3)"),
+            Ok(Literal::Number(5.0))
+        );
+    }
+
+    #[test]
+    fn closures_keep_the_variable_they_saw() {
+        let (mut interpreter, output) = capturing_interpreter();
+        execute(
+            &mut interpreter,
+            "var a = \"global\";
+            {
+                fun show() { print a; }
+                show();
+                var a = \"block\";
+                show();
+            }",
+        )
+        .unwrap();
+
+        assert_eq!(output.contents(), "global\nglobal\n");
+    }
+
+    #[test]
+    fn compound_assignment() {
+        let mut interpreter = Interpreter::new();
+        execute(
+            &mut interpreter,
+            "var x = 10; x += 5; x -= 3; x *= 2; x /= 4;",
+        )
+        .unwrap();
+
+        assert_eq!(evaluate(&mut interpreter, "x"), Ok(Literal::Number(6.0)));
+    }
+
+    #[test]
+    fn call_recursive_function() {
+        let mut interpreter = Interpreter::new();
+        execute(
+            &mut interpreter,
+            "fun fib(n) {
+                if (n == 0 or n == 1) {
+                    return n;
+                }
+
+                return fib(n-1) + fib(n-2);
+            }",
+        )
+        .unwrap();
+
+        assert_eq!(
+            evaluate(&mut interpreter, "fib(10)"),
+            Ok(Literal::Number(55.0))
+        );
+    }
+
+    #[test]
+    fn return_unwinds_nested_blocks_and_loops() {
+        let mut interpreter = Interpreter::new();
+        execute(
+            &mut interpreter,
+            "fun first_above(limit) {
+                var i = 0;
+                while (true) {
+                    { if (i > limit) return i; }
+                    i = i + 1;
+                }
+            }
+            fun no_return() {}",
+        )
+        .unwrap();
+
+        assert_eq!(
+            evaluate(&mut interpreter, "first_above(3)"),
+            Ok(Literal::Number(4.0))
+        );
+        assert_eq!(evaluate(&mut interpreter, "no_return()"), Ok(Literal::Nil));
+    }
+
+    #[test]
+    fn closures_capture_their_scope() {
+        let mut interpreter = Interpreter::new();
+        execute(
+            &mut interpreter,
+            "fun make_counter() {
+                var count = 0;
+                fun counter() {
+                    count = count + 1;
+                    return count;
+                }
+                return counter;
+            }
+            var counter = make_counter();
+            counter();",
+        )
+        .unwrap();
+
+        assert_eq!(
+            evaluate(&mut interpreter, "counter()"),
+            Ok(Literal::Number(2.0))
+        );
+    }
+
+    #[test]
+    fn call_with_wrong_arity() {
+        let mut interpreter = Interpreter::new();
+        execute(&mut interpreter, "fun f(a, b) {}").unwrap();
+        let error = evaluate(&mut interpreter, "\nf(1)").unwrap_err();
+
+        assert_eq!(error.line, 2);
+        assert_eq!(
+            error.kind,
+            RuntimeErrorKind::ArityMismatch {
+                expected: Arity::Exact(2),
+                got: 1
+            }
+        );
+    }
+
+    #[test]
+    fn call_non_callable() {
+        let mut interpreter = Interpreter::new();
+        let error = evaluate(&mut interpreter, "\"not a function\"()").unwrap_err();
+
+        assert_eq!(
+            error.kind.to_string(),
+            "Can only call functions and classes."
+        );
+    }
+
+    #[test]
+    fn call_non_callable_reports_callee_line() {
+        let mut interpreter = Interpreter::new();
+        let error = evaluate(&mut interpreter, "nil(\n1,\n2\n)").unwrap_err();
+
+        assert_eq!(error.line, 1);
+    }
+
+    #[test]
+    fn type_error_in_group_reports_its_line() {
+        let mut interpreter = Interpreter::new();
+        let error = execute(
+            &mut interpreter,
+            "var a = 1;\nprint a +\n  (2 *\n   \"x\");",
+        )
+        .unwrap_err();
+
+        assert_eq!(error.line, 3);
+    }
+
+    #[test]
+    fn unbounded_recursion_overflows_cleanly() {
+        // Run with the same stack the binary gives the interpreter.
+        let error = std::thread::Builder::new()
+            .stack_size(64 * 1024 * 1024)
+            .spawn(|| {
+                let mut interpreter = Interpreter::new();
+                execute(&mut interpreter, "fun f() {\n  f();\n}\nf();").unwrap_err()
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+
+        assert_eq!(error.kind.to_string(), "Stack overflow.");
+        assert_eq!(error.line, 2);
+    }
+
+    #[test]
+    fn call_depth_limit_is_configurable() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_max_call_depth(3);
+        let source = "fun depth(n) { if (n > 0) depth(n - 1); }";
+        execute(&mut interpreter, source).unwrap();
+
+        assert!(evaluate(&mut interpreter, "depth(2)").is_ok());
+        assert_eq!(
+            evaluate(&mut interpreter, "depth(3)")
+                .unwrap_err()
+                .kind
+                .to_string(),
+            "Stack overflow."
+        );
+    }
+
+    #[test]
+    fn clock_is_non_decreasing() {
+        let mut interpreter = Interpreter::new();
+        let first = evaluate(&mut interpreter, "clock()").unwrap();
+        let second = evaluate(&mut interpreter, "clock()").unwrap();
+
+        match (first, second) {
+            (Literal::Number(first), Literal::Number(second)) => assert!(first <= second),
+            other => panic!("expected two numbers, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn clock_checks_arity() {
+        let mut interpreter = Interpreter::new();
+        let error = evaluate(&mut interpreter, "clock(1)").unwrap_err();
+
+        assert_eq!(error.kind.to_string(), "Expected 0 arguments but got 1.");
+    }
+
+    #[test]
+    fn len_counts_characters() {
+        let mut interpreter = Interpreter::new();
+
+        assert_eq!(
+            evaluate(&mut interpreter, "len(\"abc\")"),
+            Ok(Literal::Number(3.0))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "len(\"héllo\")"),
+            Ok(Literal::Number(5.0))
+        );
+    }
+
+    #[test]
+    fn len_rejects_non_strings() {
+        let mut interpreter = Interpreter::new();
+        let error = evaluate(&mut interpreter, "len(5)").unwrap_err();
+
+        assert_eq!(
+            error.kind.to_string(),
+            "Can only take the length of a string."
+        );
+        assert_eq!(error.line, 1);
+    }
+
+    #[test]
+    fn str_converts_to_display_form() {
+        let mut interpreter = Interpreter::new();
+
+        assert_eq!(
+            evaluate(&mut interpreter, "str(12)"),
+            Ok(Literal::String("12".to_string()))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "str(nil)"),
+            Ok(Literal::String("nil".to_string()))
+        );
+    }
+
+    #[test]
+    fn num_parses_strings() {
+        let mut interpreter = Interpreter::new();
+
+        assert_eq!(
+            evaluate(&mut interpreter, "num(\"3.5\")"),
+            Ok(Literal::Number(3.5))
+        );
+    }
+
+    #[test]
+    fn num_rejects_non_numeric_strings() {
+        let mut interpreter = Interpreter::new();
+        let error = evaluate(&mut interpreter, "num(\"x\")").unwrap_err();
+
+        assert_eq!(error.kind.to_string(), "Can't convert 'x' to a number.");
+    }
+
+    #[test]
+    fn list_construction_and_indexing() {
+        let mut interpreter = Interpreter::new();
+        execute(&mut interpreter, "var list = [1, \"two\", 1 + 2];").unwrap();
+
+        assert_eq!(
+            evaluate(&mut interpreter, "list").unwrap().to_string(),
+            "[1, \"two\", 3]"
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "list[2]"),
+            Ok(Literal::Number(3.0))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "[[1, 2], [3]][0][1]"),
+            Ok(Literal::Number(2.0))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "[1, 2] == [1, 2]"),
+            Ok(Literal::Boolean(true))
+        );
+    }
+
+    #[test]
+    fn list_index_errors() {
+        let mut interpreter = Interpreter::new();
+        let message = |interpreter: &mut Interpreter, source| {
+            evaluate(interpreter, source).unwrap_err().kind.to_string()
+        };
+
+        assert_eq!(
+            message(&mut interpreter, "[1, 2][2]"),
+            "List index out of range."
+        );
+        assert_eq!(
+            message(&mut interpreter, "[1, 2][-1]"),
+            "List index out of range."
+        );
+        assert_eq!(
+            message(&mut interpreter, "[1, 2][0.5]"),
+            "List index must be an integer."
+        );
+        assert_eq!(
+            message(&mut interpreter, "[1, 2][\"0\"]"),
+            "List index must be an integer."
+        );
+        assert_eq!(
+            message(&mut interpreter, "1[0]"),
+            "Can only index into lists."
+        );
+    }
+
+    #[test]
+    fn negative_zero_equals_zero() {
+        let mut interpreter = Interpreter::new();
+
+        assert_eq!(
+            evaluate(&mut interpreter, "0 == -0"),
+            Ok(Literal::Boolean(true))
+        );
+        assert_eq!(evaluate(&mut interpreter, "-0").unwrap().to_string(), "0");
+        assert_eq!(
+            evaluate(&mut interpreter, "0 * -1").unwrap().to_string(),
+            "0"
+        );
+    }
+
+    #[test]
+    fn is_equal_compares_same_types_by_value() {
+        use Literal::*;
+
+        assert!(is_equal(&Nil, &Nil));
+        assert!(is_equal(&Number(1.0), &Number(1.0)));
+        assert!(!is_equal(&Number(1.0), &Number(2.0)));
+        assert!(!is_equal(&Number(f64::NAN), &Number(f64::NAN)));
+        assert!(is_equal(&String("a".into()), &String("a".into())));
+        assert!(!is_equal(&String("a".into()), &String("b".into())));
+        assert!(is_equal(&Boolean(true), &Boolean(true)));
+        assert!(!is_equal(&Boolean(true), &Boolean(false)));
+    }
+
+    #[test]
+    fn is_equal_rejects_mixed_types() {
+        use Literal::*;
+
+        assert!(!is_equal(&Nil, &Boolean(false)));
+        assert!(!is_equal(&Nil, &Number(0.0)));
+        assert!(!is_equal(&Number(1.0), &String("1".into())));
+        assert!(!is_equal(&Boolean(true), &Number(1.0)));
+        assert!(!is_equal(&String("".into()), &Nil));
+    }
+
+    #[test]
+    fn is_equal_compares_objects_by_identity() {
+        let mut interpreter = Interpreter::new();
+        execute(&mut interpreter, "class A {} var a = A(); var b = A();").unwrap();
+        let a = evaluate(&mut interpreter, "a").unwrap();
+        let b = evaluate(&mut interpreter, "b").unwrap();
+        let class = evaluate(&mut interpreter, "A").unwrap();
+
+        assert!(is_equal(&a, &a.clone()));
+        assert!(!is_equal(&a, &b));
+        assert!(is_equal(&class, &class.clone()));
+        assert!(!is_equal(&class, &a));
+    }
+
+    #[test]
+    fn format_substitutes_arguments() {
+        let mut interpreter = Interpreter::new();
+
+        assert_eq!(
+            evaluate(&mut interpreter, "format(\"{} + {} = {}\", 1, 2, 3)"),
+            Ok(Literal::String("1 + 2 = 3".to_string()))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "format(\"plain\")"),
+            Ok(Literal::String("plain".to_string()))
+        );
+    }
+
+    #[test]
+    fn format_rejects_placeholder_mismatch() {
+        let mut interpreter = Interpreter::new();
+        let error = evaluate(&mut interpreter, "format(\"{} and {}\", 1)").unwrap_err();
+
+        assert_eq!(
+            error.kind.to_string(),
+            "Format string has 2 placeholders but got 1 arguments."
+        );
+    }
+
+    #[test]
+    fn format_requires_format_string() {
+        let mut interpreter = Interpreter::new();
+
+        assert_eq!(
+            evaluate(&mut interpreter, "format()")
+                .unwrap_err()
+                .kind
+                .to_string(),
+            "Expected at least 1 argument but got 0."
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "format(1)")
+                .unwrap_err()
+                .kind
+                .to_string(),
+            "Can only format a string."
+        );
+    }
+
+    #[test]
+    fn max_and_min_pick_extremes() {
+        let mut interpreter = Interpreter::new();
+
+        assert_eq!(
+            evaluate(&mut interpreter, "max(1, 5, 3)"),
+            Ok(Literal::Number(5.0))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "min(2, -1)"),
+            Ok(Literal::Number(-1.0))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "max(7)"),
+            Ok(Literal::Number(7.0))
+        );
+    }
+
+    #[test]
+    fn read_line_reads_successive_lines() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_input(Box::new("first\r\nsecond\n".as_bytes()));
+
+        assert_eq!(
+            evaluate(&mut interpreter, "read_line()"),
+            Ok(Literal::String("first".to_string()))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "read_line()"),
+            Ok(Literal::String("second".to_string()))
+        );
+        assert_eq!(evaluate(&mut interpreter, "read_line()"), Ok(Literal::Nil));
+    }
+
+    #[test]
+    fn max_and_min_reject_bad_arguments() {
+        let mut interpreter = Interpreter::new();
+
+        assert_eq!(
+            evaluate(&mut interpreter, "max(\"a\", 1)")
+                .unwrap_err()
+                .kind
+                .to_string(),
+            "Can only take the maximum of numbers."
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "min(1, nil)")
+                .unwrap_err()
+                .kind
+                .to_string(),
+            "Can only take the minimum of numbers."
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "max()")
+                .unwrap_err()
+                .kind
+                .to_string(),
+            "Expected at least 1 argument but got 0."
+        );
+    }
+
+    #[test]
+    fn type_names_scalars() {
+        let mut interpreter = Interpreter::new();
+
+        for (source, name) in [
+            ("type(1.5)", "number"),
+            ("type(\"a\")", "string"),
+            ("type(true)", "bool"),
+            ("type(nil)", "nil"),
+        ] {
+            assert_eq!(
+                evaluate(&mut interpreter, source),
+                Ok(Literal::String(name.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn type_names_objects() {
+        let mut interpreter = Interpreter::new();
+        execute(&mut interpreter, "class A {} fun f() {}").unwrap();
+
+        for (source, name) in [
+            ("type(type)", "function"),
+            ("type(f)", "function"),
+            ("type(A)", "class"),
+            ("type(A())", "instance"),
+            ("type([1])", "list"),
+        ] {
+            assert_eq!(
+                evaluate(&mut interpreter, source),
+                Ok(Literal::String(name.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn is_instance_follows_superclasses() {
+        let mut interpreter = Interpreter::new();
+        execute(
+            &mut interpreter,
+            "class A {} class B < A {} class C {} var a = A(); var b = B();",
+        )
+        .unwrap();
+
+        for (source, expected) in [
+            ("is_instance(a, A)", true),
+            ("is_instance(b, B)", true),
+            ("is_instance(b, A)", true),
+            ("is_instance(a, B)", false),
+            ("is_instance(b, C)", false),
+            ("is_instance(1, A)", false),
+            ("is_instance(A, A)", false),
+        ] {
+            assert_eq!(
+                evaluate(&mut interpreter, source),
+                Ok(Literal::Boolean(expected)),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn is_instance_requires_a_class() {
+        let mut interpreter = Interpreter::new();
+        execute(&mut interpreter, "class A {}").unwrap();
+        let error = evaluate(&mut interpreter, "is_instance(A(), 1)").unwrap_err();
+
+        assert_eq!(error.kind, RuntimeErrorKind::InstanceCheckNonClass);
+        assert_eq!(
+            error.kind.to_string(),
+            "Can only check instances of a class."
+        );
+    }
+
+    #[test]
+    fn assert_passes_on_truthy_condition() {
+        let mut interpreter = Interpreter::new();
+
+        assert_eq!(
+            evaluate(&mut interpreter, "assert(true, \"ok\")"),
+            Ok(Literal::Nil)
+        );
+    }
+
+    #[test]
+    fn assert_fails_with_message() {
+        let mut interpreter = Interpreter::new();
+        let error = evaluate(&mut interpreter, "assert(1 == 2, \"math broke\")").unwrap_err();
+
+        assert_eq!(error.kind.to_string(), "math broke");
+        assert_eq!(error.exit_code, exitcode::DATAERR);
+    }
+
+    #[test]
+    fn assert_checks_arity() {
+        let mut interpreter = Interpreter::new();
+        let error = evaluate(&mut interpreter, "assert(true)").unwrap_err();
+
+        assert_eq!(error.kind.to_string(), "Expected 2 arguments but got 1.");
+    }
+
+    #[test]
+    fn class_instance_fields_and_methods() {
+        let mut interpreter = Interpreter::new();
+        execute(
+            &mut interpreter,
+            "class Counter {
+                init(start) {
+                    this.count = start;
+                }
+
+                bump() {
+                    this.count = this.count + 1;
+                    return this;
+                }
+            }
+            var counter = Counter(1);
+            counter.bump().bump();
+            counter.label = \"clicks\";",
+        )
+        .unwrap();
+
+        assert_eq!(
+            evaluate(&mut interpreter, "counter.count"),
+            Ok(Literal::Number(3.0))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "counter.label"),
+            Ok(Literal::String("clicks".to_string()))
+        );
+    }
+
+    #[test]
+    fn getter_runs_on_property_access() {
+        let mut interpreter = Interpreter::new();
+        execute(
+            &mut interpreter,
+            "class Circle {
+                init(radius) {
+                    this.radius = radius;
+                }
+
+                area {
+                    return 3 * this.radius * this.radius;
+                }
+            }
+            var circle = Circle(2);",
+        )
+        .unwrap();
+
+        assert_eq!(
+            evaluate(&mut interpreter, "circle.area"),
+            Ok(Literal::Number(12.0))
+        );
+        execute(&mut interpreter, "circle.radius = 3;").unwrap();
+        assert_eq!(
+            evaluate(&mut interpreter, "circle.area"),
+            Ok(Literal::Number(27.0))
+        );
+    }
+
+    #[test]
+    fn class_method_called_on_class() {
+        let mut interpreter = Interpreter::new();
+        execute(
+            &mut interpreter,
+            "class Math {
+                class square(n) {
+                    return n * n;
+                }
+            }
+            class Geometry < Math {}",
+        )
+        .unwrap();
+
+        assert_eq!(
+            evaluate(&mut interpreter, "Math.square(3)"),
+            Ok(Literal::Number(9.0))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "Geometry.square(4)"),
+            Ok(Literal::Number(16.0))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "Math().square")
+                .unwrap_err()
+                .kind
+                .to_string(),
+            "Undefined property 'square'."
+        );
+    }
+
+    #[test]
+    fn class_without_initializer() {
+        let mut interpreter = Interpreter::new();
+        execute(&mut interpreter, "class Empty {} var e = Empty();").unwrap();
+
+        assert_eq!(
+            evaluate(&mut interpreter, "e").map(|e| e.to_string()),
+            Ok("Empty instance".to_string())
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "Empty(1)")
+                .unwrap_err()
+                .kind
+                .to_string(),
+            "Expected 0 arguments but got 1."
+        );
+    }
+
+    #[test]
+    fn undefined_property() {
+        let mut interpreter = Interpreter::new();
+        execute(&mut interpreter, "class Empty {} var e = Empty();").unwrap();
+        let error = evaluate(&mut interpreter, "e.missing").unwrap_err();
+
+        assert_eq!(error.kind.to_string(), "Undefined property 'missing'.");
+    }
+
+    #[test]
+    fn property_access_on_non_instance() {
+        let mut interpreter = Interpreter::new();
+        let error = evaluate(&mut interpreter, "true.field").unwrap_err();
+
+        assert_eq!(error.kind.to_string(), "Only instances have properties.");
+    }
+
+    #[test]
+    fn subclass_calls_super_method() {
+        let mut interpreter = Interpreter::new();
+        execute(
+            &mut interpreter,
+            "class Greeter {
+                greet() { return \"Hello\"; }
+                name() { return \"greeter\"; }
+            }
+            class LoudGreeter < Greeter {
+                greet() { return super.greet() + \"!\"; }
+            }
+            var greeter = LoudGreeter();",
+        )
+        .unwrap();
+
+        assert_eq!(
+            evaluate(&mut interpreter, "greeter.greet()"),
+            Ok(Literal::String("Hello!".to_string()))
+        );
+        assert_eq!(
+            evaluate(&mut interpreter, "greeter.name()"),
+            Ok(Literal::String("greeter".to_string()))
+        );
+    }
+
+    #[test]
+    fn inherit_from_non_class() {
+        let mut interpreter = Interpreter::new();
+        let error = execute(&mut interpreter, "var A = 1; class B < A {}").unwrap_err();
+
+        assert_eq!(error.kind.to_string(), "Superclass must be a class.");
+    }
+
+    #[test]
+    fn while_counts_down() {
+        let (mut interpreter, output) = capturing_interpreter();
+        execute(
+            &mut interpreter,
+            "var i = 3; while (i > 0) { print i; i = i - 1; }",
+        )
+        .unwrap();
+
+        assert_eq!(output.contents(), "3\n2\n1\n");
+    }
+
+    #[test]
+    fn while_with_false_condition_never_runs() {
+        let (mut interpreter, output) = capturing_interpreter();
+        execute(&mut interpreter, "while (false) print 1;").unwrap();
+
+        assert_eq!(output.contents(), "");
+    }
+
+    const SWITCH: &str =
+        "switch (x) { case 1: print \"one\"; case 1 + 1: print \"two\"; default: print \"many\"; }";
+
+    #[test]
+    fn switch_runs_matching_case_only() {
+        let (mut interpreter, output) = capturing_interpreter();
+        execute(&mut interpreter, &format!("var x = 2; {}", SWITCH)).unwrap();
+
+        assert_eq!(output.contents(), "two\n");
+    }
+
+    #[test]
+    fn switch_runs_default_without_match() {
+        let (mut interpreter, output) = capturing_interpreter();
+        execute(&mut interpreter, &format!("var x = 3; {}", SWITCH)).unwrap();
+
+        assert_eq!(output.contents(), "many\n");
+    }
+
+    #[test]
+    fn switch_without_match_or_default_does_nothing() {
+        let (mut interpreter, output) = capturing_interpreter();
+        execute(
+            &mut interpreter,
+            "switch (\"b\") { case \"a\": print 1; } print 2;",
+        )
+        .unwrap();
+
+        assert_eq!(output.contents(), "2\n");
+    }
+
+    #[test]
+    fn switch_evaluates_discriminant_once() {
+        let (mut interpreter, output) = capturing_interpreter();
+        execute(
+            &mut interpreter,
+            "var i = 0; switch (i = i + 1) { case 0: print 0; case 1: print 1; case 2: print 2; } print i;",
+        )
+        .unwrap();
+
+        assert_eq!(output.contents(), "1\n1\n");
+    }
+
+    #[test]
+    fn switch_propagates_return() {
+        let (mut interpreter, output) = capturing_interpreter();
+        execute(
+            &mut interpreter,
+            "fun f(x) { switch (x) { case 1: return \"one\"; } return \"other\"; } print f(1); print f(2);",
+        )
+        .unwrap();
+
+        assert_eq!(output.contents(), "one\nother\n");
+    }
+
+    // A `Write` sink the test can still read from after handing a clone to
+    // the interpreter.
+    #[derive(Clone, Default)]
+    pub(crate) struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl SharedBuffer {
+        pub(crate) fn contents(&self) -> String {
+            String::from_utf8(self.0.borrow().clone()).unwrap()
+        }
+    }
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn capturing_interpreter() -> (Interpreter, SharedBuffer) {
+        let output = SharedBuffer::default();
+        (Interpreter::with_output(Box::new(output.clone())), output)
+    }
+
+    fn execute(interpreter: &mut Interpreter, source: &str) -> Result<(), RuntimeError> {
+        let mut error_reporter = ErrorReporter::new();
+        let tokens = Scanner::new(source.to_string(), &mut error_reporter).scan_tokens();
+        let statements = Parser::new(&tokens).parse_program().unwrap();
+        Resolver::new(interpreter).resolve(&statements).unwrap();
+        interpreter.interpret(&statements)
+    }
+
+    fn evaluate(interpreter: &mut Interpreter, source: &str) -> Result<Literal, RuntimeError> {
+        let mut error_reporter = ErrorReporter::new();
+        let tokens = Scanner::new(source.to_string(), &mut error_reporter).scan_tokens();
+        let expr = Parser::new(&tokens).parse().unwrap();
+        Resolver::new(interpreter)
+            .resolve_expression(&expr)
+            .unwrap();
+        interpreter.visit_expr(&expr)
     }
 }
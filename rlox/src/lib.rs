@@ -0,0 +1,237 @@
+use std::io::{self, Write};
+
+use exitcode::{self, ExitCode};
+
+use error_reporter::{Diagnostic, ErrorReporter, ParseError, RuntimeError};
+use grammar::{Literal, Stmt};
+use interpreter::Interpreter;
+use parser::Parser;
+use resolver::Resolver;
+use scanner::Scanner;
+use token::Token;
+
+pub mod ast_printer;
+pub mod callable;
+pub mod class;
+pub mod environment;
+pub mod error_reporter;
+pub mod grammar;
+pub mod interpreter;
+pub mod natives;
+pub mod parser;
+pub mod resolver;
+pub mod scanner;
+pub mod token;
+pub mod visitor;
+
+// Runs `source` to completion, handing back every diagnostic if it fails.
+pub fn interpret(source: &str) -> Result<(), Vec<Diagnostic>> {
+    let mut error_reporter = ErrorReporter::with_output(Box::new(io::sink()));
+
+    match run(source, &mut error_reporter, &mut Interpreter::new()) {
+        exitcode::OK => Ok(()),
+        _ => Err(error_reporter.diagnostics().to_vec()),
+    }
+}
+
+// Scans, parses and runs `source` on `interpreter`, sending every problem to
+// `error_reporter`.
+pub fn run(
+    source: &str,
+    error_reporter: &mut ErrorReporter,
+    interpreter: &mut Interpreter,
+) -> ExitCode {
+    match parse(source, error_reporter) {
+        Some(statements) => execute(&statements, error_reporter, interpreter),
+        None => error_reporter.exit_code.unwrap(),
+    }
+}
+
+// Resolves and runs already parsed `statements` on `interpreter`.
+pub fn execute(
+    statements: &[Stmt],
+    error_reporter: &mut ErrorReporter,
+    interpreter: &mut Interpreter,
+) -> ExitCode {
+    if let Err(errors) = Resolver::new(interpreter).resolve(statements) {
+        return report_static_errors(error_reporter, &errors);
+    }
+
+    match interpreter.interpret(statements) {
+        Ok(()) => exitcode::OK,
+        Err(error) => report_runtime_error(error_reporter, error),
+    }
+}
+
+// A program parsed once that can run any number of times, each time from
+// fresh globals, without scanning and parsing it again.
+pub struct ParsedProgram {
+    statements: Vec<Stmt>,
+}
+
+impl ParsedProgram {
+    // Returns `None` once any error is reported.
+    pub fn parse(source: &str, error_reporter: &mut ErrorReporter) -> Option<ParsedProgram> {
+        let statements = parse(source, error_reporter)?;
+        Some(ParsedProgram { statements })
+    }
+
+    pub fn execute(&self, error_reporter: &mut ErrorReporter) -> ExitCode {
+        self.execute_with_output(error_reporter, Box::new(io::stdout()))
+    }
+
+    // Like `execute`, but `print` writes to `output`.
+    pub fn execute_with_output(
+        &self,
+        error_reporter: &mut ErrorReporter,
+        output: Box<dyn Write>,
+    ) -> ExitCode {
+        // Resolution is redone each time since it's recorded on the
+        // interpreter.
+        let mut interpreter = Interpreter::with_output(output);
+        execute(&self.statements, error_reporter, &mut interpreter)
+    }
+}
+
+// Scans, parses and resolves `source` without running it, reporting every
+// problem found along the way.
+pub fn check(source: &str, error_reporter: &mut ErrorReporter) -> ExitCode {
+    let statements = match parse(source, error_reporter) {
+        Some(s) => s,
+        None => return error_reporter.exit_code.unwrap(),
+    };
+    // The resolver records what it finds on an interpreter, but nothing runs,
+    // so a throwaway one will do.
+    match Resolver::new(&mut Interpreter::new()).resolve(&statements) {
+        Ok(()) => exitcode::OK,
+        Err(errors) => report_static_errors(error_reporter, &errors),
+    }
+}
+
+// Runs one line typed at the REPL against an interpreter that lives across
+// lines. A bare expression such as `1 + 2` is evaluated and its value handed
+// back for printing; anything else runs as statements and yields `None`.
+pub fn run_line(
+    source: &str,
+    error_reporter: &mut ErrorReporter,
+    interpreter: &mut Interpreter,
+) -> Result<Option<Literal>, ExitCode> {
+    let tokens = match scan(source, error_reporter) {
+        Some(t) => t,
+        None => return Err(error_reporter.exit_code.unwrap()),
+    };
+
+    if let Some(expr) = Parser::new(&tokens).parse_lone_expression() {
+        if let Err(errors) = Resolver::new(interpreter).resolve_expression(&expr) {
+            return Err(report_static_errors(error_reporter, &errors));
+        }
+        return interpreter
+            .evaluate(&expr)
+            .map(Some)
+            .map_err(|error| report_runtime_error(error_reporter, error));
+    }
+
+    let statements = match parse_tokens(&tokens, error_reporter) {
+        Some(s) => s,
+        None => return Err(error_reporter.exit_code.unwrap()),
+    };
+    if let Err(errors) = Resolver::new(interpreter).resolve(&statements) {
+        return Err(report_static_errors(error_reporter, &errors));
+    }
+    interpreter
+        .interpret(&statements)
+        .map(|()| None)
+        .map_err(|error| report_runtime_error(error_reporter, error))
+}
+
+fn report_runtime_error(error_reporter: &mut ErrorReporter, error: RuntimeError) -> ExitCode {
+    let RuntimeError {
+        line,
+        kind,
+        exit_code,
+    } = error;
+    error_reporter.runtime_error(line, &kind.to_string(), exit_code);
+    exit_code
+}
+
+// Scans `source`, returning `None` if it contains any lexical errors.
+pub fn scan(source: &str, error_reporter: &mut ErrorReporter) -> Option<Vec<Token>> {
+    error_reporter.set_source(source);
+    let scanner = Scanner::new(source.to_string(), error_reporter);
+    let tokens = scanner.scan_tokens();
+
+    if error_reporter.had_error {
+        error_reporter.report_summary();
+        return None;
+    }
+
+    Some(tokens)
+}
+
+// Scans and parses `source`, returning `None` once any error is reported.
+pub fn parse(source: &str, error_reporter: &mut ErrorReporter) -> Option<Vec<Stmt>> {
+    let tokens = scan(source, error_reporter)?;
+    parse_tokens(&tokens, error_reporter)
+}
+
+// Parses tokens from `scan`, returning `None` once any error is reported.
+pub fn parse_tokens(tokens: &[Token], error_reporter: &mut ErrorReporter) -> Option<Vec<Stmt>> {
+    let mut parser = Parser::new(tokens);
+    let result = parser.parse_program();
+    for warning in parser.warnings() {
+        error_reporter.warn(
+            warning.line,
+            warning.column,
+            warning.visual_column,
+            &warning.message,
+        );
+    }
+
+    match result {
+        Ok(s) => Some(s),
+        Err(errors) => {
+            report_static_errors(error_reporter, &errors);
+            None
+        }
+    }
+}
+
+// Reports errors found before running anything, by the parser or resolver.
+fn report_static_errors(error_reporter: &mut ErrorReporter, errors: &[ParseError]) -> ExitCode {
+    for error in errors {
+        error_reporter.parse_error(error);
+    }
+    error_reporter.report_summary();
+    error_reporter.exit_code.unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpreter::tests::SharedBuffer;
+
+    #[test]
+    fn parsed_program_runs_from_fresh_globals_each_time() {
+        let mut error_reporter = ErrorReporter::new();
+        // A shared interpreter would print the second run with precision 2.
+        let source = "var runs = 0; runs = runs + 1; print runs; print 1 / 3; setprecision(2);";
+        let program = ParsedProgram::parse(source, &mut error_reporter).unwrap();
+
+        for _ in 0..2 {
+            let output = SharedBuffer::default();
+            let exit_code =
+                program.execute_with_output(&mut error_reporter, Box::new(output.clone()));
+
+            assert_eq!(exit_code, exitcode::OK);
+            assert_eq!(output.contents(), "1\n0.3333333333333333\n");
+        }
+    }
+
+    #[test]
+    fn parsed_program_reports_parse_errors() {
+        let mut error_reporter = ErrorReporter::with_output(Box::new(io::sink()));
+
+        assert!(ParsedProgram::parse("print ;", &mut error_reporter).is_none());
+        assert_eq!(error_reporter.exit_code, Some(exitcode::DATAERR));
+    }
+}
@@ -4,106 +4,333 @@ use std::{
     cmp::Ordering,
     env::args,
     fs,
-    io::{self, Write},
+    io::{self, BufRead, BufReader, Write},
+    thread,
+    time::Instant,
 };
 
-use error_reporter::ErrorReporter;
-use scanner::Scanner;
+use rlox::{ast_printer::AstPrinter, error_reporter::ErrorReporter, interpreter::Interpreter};
 
-use crate::{interpreter::Interpreter, parser::Parser, visitor::Visitor};
-
-pub mod error_reporter;
-pub mod grammar;
-pub mod interpreter;
-pub mod parser;
-pub mod scanner;
-pub mod token;
-pub mod visitor;
+// Deep Lox recursion needs much more native stack than the main thread gets,
+// enough to reach the interpreter's call depth limit in a debug build.
+const STACK_SIZE: usize = 64 * 1024 * 1024;
 
 fn main() {
-    let exit_code = run();
+    let exit_code = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(run)
+        .expect("Failed to start the interpreter thread.")
+        .join()
+        .unwrap_or(exitcode::SOFTWARE);
     std::process::exit(exit_code);
 }
 
 fn run() -> ExitCode {
-    let mut interpreter = Program::default();
+    run_args(args().skip(1))
+}
 
-    let args = args().collect::<Vec<_>>();
-    let args_len = args.len();
+// Flags may appear anywhere; `--eval` takes the source to run as its value
+// and replaces the script argument.
+fn run_args<I: IntoIterator<Item = String>>(args: I) -> ExitCode {
+    let mut interpreter = Program::default();
+    let mut eval = None;
+    let mut scripts = Vec::new();
 
-    match args_len.cmp(&2) {
-        Ordering::Greater => {
-            eprintln!("Usage: rlox [script]");
-            return exitcode::USAGE;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--ast" => interpreter.mode = Mode::PrintAst,
+            "--check" => interpreter.mode = Mode::Check,
+            "--tokens" => interpreter.mode = Mode::PrintTokens,
+            "--json" => interpreter.mode = Mode::PrintJson,
+            "--time" => interpreter.time = true,
+            "--eval" => match args.next() {
+                Some(source) if eval.is_none() => eval = Some(source),
+                _ => return usage(),
+            },
+            flag if flag.starts_with("--") => return usage(),
+            _ => scripts.push(arg),
         }
-        Ordering::Equal => interpreter.run_file(args[1].clone()),
-        Ordering::Less => interpreter.run_prompt(),
-    };
+    }
 
-    exitcode::OK
+    match (eval, scripts.len().cmp(&1)) {
+        (Some(source), Ordering::Less) => interpreter.run(source),
+        (Some(_), _) | (None, Ordering::Greater) => usage(),
+        (None, Ordering::Equal) => interpreter.run_file(scripts.remove(0)),
+        // Not `stdin().lock()`: holding the lock for the whole session would
+        // deadlock the first `read_line()` call.
+        (None, Ordering::Less) => interpreter.run_prompt(&mut BufReader::new(io::stdin())),
+    }
 }
 
-struct Program {
-    error_reporter: ErrorReporter,
+fn usage() -> ExitCode {
+    eprintln!(
+        "Usage: rlox [--ast | --check | --json | --tokens] [--time] [script | --eval source]"
+    );
+    exitcode::USAGE
 }
 
-impl Default for Program {
-    fn default() -> Self {
-        Program {
-            error_reporter: ErrorReporter::new(),
-        }
-    }
+// What to do with each piece of source handed to the program.
+#[derive(Default)]
+enum Mode {
+    #[default]
+    Run,
+    // Print the syntax tree of each statement instead of running it.
+    PrintAst,
+    // Print each token on its own line without parsing.
+    PrintTokens,
+    // Print the syntax tree of the whole program as JSON.
+    PrintJson,
+    // Report problems found before running, without running anything.
+    Check,
+}
+
+#[derive(Default)]
+struct Program {
+    error_reporter: ErrorReporter,
+    mode: Mode,
+    // Whether running source also reports how long each phase took.
+    time: bool,
+    // Lives across REPL lines so earlier definitions stay visible.
+    interpreter: Interpreter,
 }
 
 impl Program {
-    fn run_file(&mut self, file_path: String) {
-        let source = fs::read_to_string(file_path).unwrap();
-        self.run(source);
+    // Each script starts from a fresh global environment.
+    fn run_file(&mut self, file_path: String) -> ExitCode {
+        match fs::read_to_string(&file_path) {
+            Ok(source) => {
+                self.interpreter = Interpreter::new();
+                self.run(source)
+            }
+            Err(error) => {
+                eprintln!("Could not read file '{}': {}", file_path, error);
+                exitcode::NOINPUT
+            }
+        }
     }
 
-    fn run_prompt(&mut self) {
+    fn run_prompt<R: BufRead>(&mut self, input: &mut R) -> ExitCode {
         let mut line = String::new();
 
         loop {
             print!("> ");
             io::stdout().flush().unwrap();
 
-            io::stdin().read_line(&mut line).unwrap();
+            match input.read_line(&mut line) {
+                // EOF, e.g. Ctrl-D or the end of a piped script.
+                Ok(0) => {
+                    println!();
+                    return exitcode::OK;
+                }
+                Ok(_) => (),
+                Err(error) => {
+                    eprintln!("Could not read input: {}", error);
+                    return exitcode::IOERR;
+                }
+            }
             line = line.trim_end().to_string();
 
-            self.run(line.clone());
-            self.error_reporter.had_error = false;
+            self.run_line(line.clone());
 
             line.clear();
         }
     }
 
     fn run(&mut self, source: String) -> ExitCode {
-        let mut scanner = Scanner::new(source, &mut self.error_reporter);
-        let tokens = scanner.scan_tokens();
-
-        if self.error_reporter.had_error {
-            return self.error_reporter.exit_code.unwrap();
+        self.error_reporter = ErrorReporter::with_source(&source);
+        match self.mode {
+            Mode::Run if self.time => self.run_timed(&source),
+            Mode::Run => rlox::run(&source, &mut self.error_reporter, &mut self.interpreter),
+            Mode::PrintAst => self.print_ast(&source),
+            Mode::PrintTokens => self.print_tokens(&source),
+            Mode::PrintJson => self.print_json(&source),
+            Mode::Check => rlox::check(&source, &mut self.error_reporter),
         }
+    }
 
-        let mut parser = Parser::new(tokens, &mut self.error_reporter);
-        let expr = match parser.parse() {
-            Some(e) => e,
-            None => return self.error_reporter.exit_code.unwrap(),
+    // Like `run`, but a bare expression prints its value, and state carries
+    // over to the next line.
+    fn run_line(&mut self, source: String) -> ExitCode {
+        let Mode::Run = self.mode else {
+            return self.run(source);
         };
 
-        let interpreter = Interpreter;
-        let literal = match interpreter.visit_expr(&expr) {
-            Ok(l) => l,
-            Err(e) => {
-                self.error_reporter
-                    .runtime_error(e.line, &e.message, e.exit_code);
-                return self.error_reporter.exit_code.unwrap();
+        self.error_reporter = ErrorReporter::with_source(&source);
+        match rlox::run_line(&source, &mut self.error_reporter, &mut self.interpreter) {
+            Ok(Some(value)) => {
+                println!("{}", value);
+                exitcode::OK
             }
+            Ok(None) => exitcode::OK,
+            Err(exit_code) => exit_code,
+        }
+    }
+
+    // Runs `source` like `run`, then writes how long each phase took to
+    // stderr. Resolving variables counts towards interpreting.
+    fn run_timed(&mut self, source: &str) -> ExitCode {
+        let start = Instant::now();
+        let tokens = rlox::scan(source, &mut self.error_reporter);
+        let scanned = Instant::now();
+        let statements =
+            tokens.and_then(|tokens| rlox::parse_tokens(&tokens, &mut self.error_reporter));
+        let parsed = Instant::now();
+        let exit_code = match statements {
+            Some(statements) => {
+                rlox::execute(&statements, &mut self.error_reporter, &mut self.interpreter)
+            }
+            None => self.error_reporter.exit_code.unwrap(),
         };
+        let interpreted = Instant::now();
+
+        eprintln!("scan:      {:?}", scanned - start);
+        eprintln!("parse:     {:?}", parsed - scanned);
+        eprintln!("interpret: {:?}", interpreted - parsed);
+        exit_code
+    }
+
+    fn print_tokens(&mut self, source: &str) -> ExitCode {
+        match rlox::scan(source, &mut self.error_reporter) {
+            Some(tokens) => {
+                for token in &tokens {
+                    println!("{}", token);
+                }
+                exitcode::OK
+            }
+            None => self.error_reporter.exit_code.unwrap(),
+        }
+    }
+
+    fn print_json(&mut self, source: &str) -> ExitCode {
+        match rlox::parse(source, &mut self.error_reporter) {
+            Some(statements) => {
+                let json = serde_json::to_string_pretty(&statements)
+                    .expect("Syntax trees always serialize.");
+                println!("{}", json);
+                exitcode::OK
+            }
+            None => self.error_reporter.exit_code.unwrap(),
+        }
+    }
+
+    fn print_ast(&mut self, source: &str) -> ExitCode {
+        match rlox::parse(source, &mut self.error_reporter) {
+            Some(statements) => {
+                for statement in &statements {
+                    println!("{}", AstPrinter.print_stmt(statement));
+                }
+                exitcode::OK
+            }
+            None => self.error_reporter.exit_code.unwrap(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    // Keeps what the program prints out of the test output.
+    fn quiet_program() -> Program {
+        Program {
+            interpreter: Interpreter::with_output(Box::new(io::sink())),
+            ..Program::default()
+        }
+    }
+
+    #[test]
+    fn eval_runs_source_argument() {
+        assert_eq!(run_args(args(&["--eval", "var x = 1 + 2;"])), exitcode::OK);
+        assert_eq!(run_args(args(&["--eval", "print x;"])), exitcode::DATAERR);
+        assert_eq!(
+            run_args(args(&["--ast", "--eval", "1 +"])),
+            exitcode::DATAERR
+        );
+    }
+
+    #[test]
+    fn eval_without_source_is_usage_error() {
+        assert_eq!(run_args(args(&["--eval"])), exitcode::USAGE);
+        assert_eq!(
+            run_args(args(&["--eval", "1;", "script.lox"])),
+            exitcode::USAGE
+        );
+    }
+
+    #[test]
+    fn check_reports_without_running() {
+        assert_eq!(
+            run_args(args(&["--check", "--eval", "print 1;"])),
+            exitcode::OK
+        );
+        assert_eq!(
+            run_args(args(&["--check", "--eval", "print x;"])),
+            exitcode::OK
+        );
+        assert_eq!(
+            run_args(args(&["--check", "--eval", "print 1"])),
+            exitcode::DATAERR
+        );
+        assert_eq!(
+            run_args(args(&["--check", "--eval", "return 1;"])),
+            exitcode::DATAERR
+        );
+    }
+
+    #[test]
+    fn run_file_reports_missing_file() {
+        let exit_code = Program::default().run_file("no/such/script.lox".to_string());
+
+        assert_eq!(exit_code, exitcode::NOINPUT);
+    }
+
+    #[test]
+    fn run_prompt_stops_at_eof() {
+        let mut input = "var a = 1;\nprint a;\n".as_bytes();
+
+        assert_eq!(quiet_program().run_prompt(&mut input), exitcode::OK);
+    }
+
+    #[test]
+    fn run_line_keeps_state_between_lines() {
+        let mut program = Program::default();
+
+        assert_eq!(program.run_line("var x = 1;".to_string()), exitcode::OK);
+        assert_eq!(program.run_line("x + 1".to_string()), exitcode::OK);
+        assert_eq!(program.run_line("y".to_string()), exitcode::DATAERR);
+    }
+
+    #[test]
+    fn run_prompt_remembers_definitions() {
+        let mut program = quiet_program();
+        let mut input = "var a = 1;\n".as_bytes();
+        program.run_prompt(&mut input);
+
+        assert_eq!(program.run_line("print a;".to_string()), exitcode::OK);
+    }
+
+    #[test]
+    fn run_file_starts_from_fresh_globals() {
+        let path = std::env::temp_dir().join("rlox_run_file_starts_from_fresh_globals.lox");
+        fs::write(&path, "print a;").unwrap();
+
+        let mut program = Program::default();
+        program.run_line("var a = 1;".to_string());
+        let exit_code = program.run_file(path.to_string_lossy().into_owned());
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(exit_code, exitcode::DATAERR);
+    }
 
-        println!("{}", literal);
+    #[test]
+    fn run_prompt_stops_at_empty_input() {
+        let mut input = io::empty();
 
-        exitcode::OK
+        assert_eq!(Program::default().run_prompt(&mut input), exitcode::OK);
     }
 }
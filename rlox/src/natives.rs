@@ -0,0 +1,232 @@
+use std::{
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    callable::{Arity, NativeFn, NativeFunction},
+    environment::Environment,
+    error_reporter::{RuntimeError, RuntimeErrorKind},
+    grammar::Literal,
+    interpreter::{is_truthy, Interpreter},
+};
+
+// Registers every native function in the global environment.
+pub fn define_natives(globals: &mut Environment) {
+    define(globals, "clock", Arity::Exact(0), clock);
+    define(globals, "len", Arity::Exact(1), len);
+    define(globals, "str", Arity::Exact(1), str);
+    define(globals, "num", Arity::Exact(1), num);
+    define(globals, "assert", Arity::Exact(2), assert);
+    define(globals, "setprecision", Arity::Exact(1), setprecision);
+    define(globals, "type", Arity::Exact(1), type_of);
+    define(globals, "format", Arity::AtLeast(1), format);
+    define(globals, "max", Arity::AtLeast(1), max);
+    define(globals, "min", Arity::AtLeast(1), min);
+    define(globals, "read_line", Arity::Exact(0), read_line);
+    define(globals, "is_instance", Arity::Exact(2), is_instance);
+}
+
+fn define(globals: &mut Environment, name: &'static str, arity: Arity, function: NativeFn) {
+    let native = NativeFunction::new(name, arity, function);
+    globals.define(name.to_string(), Literal::Callable(Rc::new(native)));
+}
+
+// Seconds since the Unix epoch.
+fn clock(_: &mut Interpreter, _: Vec<Literal>, _: i32) -> Result<Literal, RuntimeError> {
+    let elapsed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("System clock is set before the Unix epoch.");
+
+    Ok(Literal::Number(elapsed.as_secs_f64()))
+}
+
+// The length of a string in Unicode scalar values (Rust `char`s), matching
+// how the scanner reads source text, so `len("é")` is 1 rather than 2.
+fn len(_: &mut Interpreter, arguments: Vec<Literal>, line: i32) -> Result<Literal, RuntimeError> {
+    match &arguments[0] {
+        Literal::String(string) => Ok(Literal::Number(string.chars().count() as f64)),
+        _ => Err(RuntimeError::new(line, RuntimeErrorKind::LengthOfNonString)),
+    }
+}
+
+// Converts any value to the string `print` would show for it.
+fn str(_: &mut Interpreter, arguments: Vec<Literal>, _: i32) -> Result<Literal, RuntimeError> {
+    Ok(Literal::String(arguments[0].to_string()))
+}
+
+// Parses a string such as "3.5" into a number. Surrounding whitespace is
+// ignored, but anything that doesn't parse to a finite number is an error.
+fn num(_: &mut Interpreter, arguments: Vec<Literal>, line: i32) -> Result<Literal, RuntimeError> {
+    let parsed = match &arguments[0] {
+        Literal::String(string) => string.trim().parse::<f64>().ok(),
+        _ => None,
+    };
+
+    match parsed {
+        Some(number) if number.is_finite() => Ok(Literal::Number(number)),
+        _ => Err(RuntimeError::new(
+            line,
+            RuntimeErrorKind::InvalidNumber(arguments[0].to_string()),
+        )),
+    }
+}
+
+// Does nothing when the condition is truthy; otherwise fails with the given
+// message, so Lox scripts can check their own results.
+fn assert(
+    _: &mut Interpreter,
+    arguments: Vec<Literal>,
+    line: i32,
+) -> Result<Literal, RuntimeError> {
+    if is_truthy(&arguments[0]) {
+        return Ok(Literal::Nil);
+    }
+
+    match &arguments[1] {
+        Literal::String(message) => Err(RuntimeError::new(
+            line,
+            RuntimeErrorKind::AssertionFailed(message.clone()),
+        )),
+        _ => Err(RuntimeError::new(
+            line,
+            RuntimeErrorKind::InvalidAssertionMessage,
+        )),
+    }
+}
+
+// Sets how many fractional digits `print` shows for numbers; `nil` restores
+// the default format.
+fn setprecision(
+    interpreter: &mut Interpreter,
+    arguments: Vec<Literal>,
+    line: i32,
+) -> Result<Literal, RuntimeError> {
+    let precision = match &arguments[0] {
+        Literal::Nil => None,
+        precision => match precision.as_i64().map(usize::try_from) {
+            Some(Ok(precision)) => Some(precision),
+            _ => return Err(RuntimeError::new(line, RuntimeErrorKind::InvalidPrecision)),
+        },
+    };
+    interpreter
+        .set_print_precision(precision)
+        .map_err(|kind| RuntimeError::new(line, kind))?;
+
+    Ok(Literal::Nil)
+}
+
+// The next line of input, or nil once it runs out.
+fn read_line(
+    interpreter: &mut Interpreter,
+    _: Vec<Literal>,
+    line: i32,
+) -> Result<Literal, RuntimeError> {
+    match interpreter.read_line() {
+        Ok(Some(input)) => Ok(Literal::String(input)),
+        Ok(None) => Ok(Literal::Nil),
+        Err(error) => Err(RuntimeError::new(
+            line,
+            RuntimeErrorKind::ReadFailed(error.to_string()),
+        )),
+    }
+}
+
+// Whether a value is an instance of a class or of one of its subclasses.
+fn is_instance(
+    _: &mut Interpreter,
+    arguments: Vec<Literal>,
+    line: i32,
+) -> Result<Literal, RuntimeError> {
+    let Literal::Class(class) = &arguments[1] else {
+        return Err(RuntimeError::new(
+            line,
+            RuntimeErrorKind::InstanceCheckNonClass,
+        ));
+    };
+
+    Ok(Literal::Boolean(match &arguments[0] {
+        Literal::Instance(instance) => instance.borrow().class().is_subclass_of(class),
+        _ => false,
+    }))
+}
+
+// Names the kind of value it's given, e.g. "number" or "instance".
+fn type_of(_: &mut Interpreter, arguments: Vec<Literal>, _: i32) -> Result<Literal, RuntimeError> {
+    let name = match &arguments[0] {
+        Literal::Number(_) => "number",
+        Literal::String(_) => "string",
+        Literal::Boolean(_) => "bool",
+        Literal::Nil => "nil",
+        Literal::Callable(_) => "function",
+        Literal::Class(_) => "class",
+        Literal::Instance(_) => "instance",
+        Literal::List(_) => "list",
+    };
+
+    Ok(Literal::String(name.to_string()))
+}
+
+// Replaces each `{}` in the format string with the next argument, shown the
+// way `print` would show it. There is no escape for a literal `{}`.
+fn format(
+    _: &mut Interpreter,
+    arguments: Vec<Literal>,
+    line: i32,
+) -> Result<Literal, RuntimeError> {
+    let Literal::String(template) = &arguments[0] else {
+        return Err(RuntimeError::new(line, RuntimeErrorKind::FormatNonString));
+    };
+
+    let pieces = template.split("{}").collect::<Vec<_>>();
+    let values = &arguments[1..];
+    if pieces.len() - 1 != values.len() {
+        return Err(RuntimeError::new(
+            line,
+            RuntimeErrorKind::FormatArgumentCount {
+                placeholders: pieces.len() - 1,
+                got: values.len(),
+            },
+        ));
+    }
+
+    let mut result = pieces[0].to_string();
+    for (value, piece) in values.iter().zip(&pieces[1..]) {
+        result.push_str(&value.to_string());
+        result.push_str(piece);
+    }
+
+    Ok(Literal::String(result))
+}
+
+// The largest of one or more numbers.
+fn max(_: &mut Interpreter, arguments: Vec<Literal>, line: i32) -> Result<Literal, RuntimeError> {
+    fold_numbers(&arguments, line, "maximum", f64::max)
+}
+
+// The smallest of one or more numbers.
+fn min(_: &mut Interpreter, arguments: Vec<Literal>, line: i32) -> Result<Literal, RuntimeError> {
+    fold_numbers(&arguments, line, "minimum", f64::min)
+}
+
+fn fold_numbers(
+    arguments: &[Literal],
+    line: i32,
+    what: &'static str,
+    pick: fn(f64, f64) -> f64,
+) -> Result<Literal, RuntimeError> {
+    let mut result = None;
+    for argument in arguments {
+        let Literal::Number(n) = argument else {
+            return Err(RuntimeError::new(
+                line,
+                RuntimeErrorKind::ExtremumOfNonNumber(what),
+            ));
+        };
+        result = Some(result.map_or(*n, |result| pick(result, *n)));
+    }
+
+    Ok(Literal::Number(
+        result.expect("max and min take at least one argument"),
+    ))
+}
@@ -1,69 +1,621 @@
 // New Grammar:
-// expression     → equality ;
+// program        → declaration* EOF ;
+// declaration    → classDecl
+//                | funDecl
+//                | varDecl
+//                | statement ;
+// classDecl      → "class" IDENTIFIER ( "<" IDENTIFIER )?
+//                  "{" ( "class"? method )* "}" ;
+// funDecl        → "fun" function ;
+// function       → IDENTIFIER "(" parameters? ")" block ;
+// method         → IDENTIFIER ( "(" parameters? ")" )? block ;
+// parameters     → IDENTIFIER ( "," IDENTIFIER )* ","? ;
+// varDecl        → "var" IDENTIFIER ( "=" expression )? ";" ;
+// statement      → exprStmt
+//                | forStmt
+//                | ifStmt
+//                | printStmt
+//                | returnStmt
+//                | whileStmt
+//                | switchStmt
+//                | breakStmt
+//                | continueStmt
+//                | block ;
+// forStmt        → "for" "(" ( varDecl | exprStmt | ";" )
+//                  expression? ";"
+//                  expression? ")" statement ;
+// ifStmt         → "if" "(" expression ")" statement
+//                  ( "else" statement )? ;
+// whileStmt      → "while" "(" expression ")" statement ;
+// switchStmt     → "switch" "(" expression ")" "{"
+//                  ( "case" expression ":" declaration* )*
+//                  ( "default" ":" declaration* )? "}" ;
+// block          → "{" declaration* "}" ;
+// exprStmt       → expression ";" ;
+// printStmt      → "print" expression ";" ;
+// returnStmt     → "return" expression? ";" ;
+// breakStmt      → "break" ";" ;
+// continueStmt   → "continue" ";" ;
+// expression     → comma ;
+// comma          → assignment ( "," assignment )* ;
+// assignment     → ( call "." )? IDENTIFIER "=" assignment
+//                | IDENTIFIER ( "+=" | "-=" | "*=" | "/=" ) assignment
+//                | ternary ;
+// ternary        → coalesce ( "?" expression ":" ternary )? ;
+// coalesce       → logic_or ( "??" logic_or )* ;
+// logic_or       → logic_and ( "or" logic_and )* ;
+// logic_and      → equality ( "and" equality )* ;
 // equality       → comparison ( ( "!=" | "==" ) comparison )* ;
 // comparison     → term ( ( ">" | ">=" | "<" | "<=" ) term )* ;
 // term           → factor ( ( "-" | "+" ) factor )* ;
-// factor         → unary ( ( "/" | "*" ) unary )* ;
-// unary          → ( "!" | "-" ) unary
-//                | primary ;
+// factor         → unary ( ( "/" | "*" | "%" | "~/" ) unary )* ;
+// unary          → ( "!" | "-" | "++" | "--" ) unary
+//                | power ;
+// power          → call ( "**" unary )? ;
+// call           → primary ( "(" arguments? ")" | "." IDENTIFIER
+//                          | "[" expression "]" )* ( "++" | "--" )? ;
+// arguments      → assignment ( "," assignment )* ","? ;
 // primary        → NUMBER | STRING | "true" | "false" | "nil"
-//                | "(" expression ")" ;
+//                | "(" expression ")" | IDENTIFIER | "this"
+//                | "[" arguments? "]"
+//                | "fun" "(" parameters? ")" block
+//                | "super" "." IDENTIFIER
+//                // Error productions for a missing left-hand operand.
+//                | ( "!=" | "==" ) comparison
+//                | ( ">" | ">=" | "<" | "<=" ) term
+//                | "+" factor
+//                | ( "/" | "*" | "%" | "~/" ) unary ;
+
+use std::{mem, rc::Rc};
 
 use crate::{
-    error_reporter::ErrorReporter,
-    grammar::{BinaryExpr, Expr, LiteralExpr, UnaryExpr},
+    error_reporter::ParseError,
+    grammar::{
+        AssignExpr, BinaryExpr, BlockStmt, BreakStmt, CallExpr, ClassStmt, ContinueStmt, Expr,
+        ExpressionStmt, FunctionExpr, FunctionStmt, GetExpr, GroupingExpr, IfStmt, IndexExpr,
+        ListExpr, LiteralExpr, LogicalExpr, PrintStmt, ReturnStmt, SetExpr, Stmt, SuperExpr,
+        SwitchCase, SwitchStmt, TernaryExpr, ThisExpr, UnaryExpr, UpdateExpr, VarStmt,
+        VariableExpr, WhileStmt,
+    },
     token::{
         Token,
         TokenType::{self, *},
     },
 };
 
+type Methods = Vec<Rc<FunctionStmt>>;
+
+#[derive(Clone, Copy, PartialEq)]
+enum ClassType {
+    None,
+    Class,
+    Subclass,
+}
+
 pub struct Parser<'a> {
-    tokens: Vec<Token>,
+    tokens: &'a [Token],
     // Current token to be consumed.
     curr: usize,
-
-    error_reporter: &'a mut ErrorReporter,
+    // The kind of class body being parsed, used to validate `super`.
+    current_class: ClassType,
+    // Whether the code being parsed is inside a class method, where there is
+    // no `this` or `super`.
+    in_class_method: bool,
+    // How many loops enclose the statement being parsed, used to validate
+    // `break` and `continue`.
+    loop_depth: usize,
+    // The operator of the assignment parsed last. Assignment is
+    // right-associative, so for a whole assignment this is its outermost
+    // operator, used to warn about `=` in conditions.
+    last_assignment_op: Option<&'a Token>,
+    // Problems that don't stop the program from running, such as code that
+    // can never run. They share the shape of errors.
+    warnings: Vec<ParseError>,
+    // Errors reported so far, handed back to the caller once parsing ends.
+    errors: Vec<ParseError>,
 }
 
+// The most arguments a call may pass, and parameters a function may take.
+const MAX_ARGUMENTS: usize = 255;
+
 impl<'a> Parser<'a> {
-    pub fn new(tokens: Vec<Token>, error_reporter: &'a mut ErrorReporter) -> Self {
+    pub fn new(tokens: &'a [Token]) -> Self {
         Self {
             tokens,
             curr: 0,
-            error_reporter,
+            current_class: ClassType::None,
+            in_class_method: false,
+            loop_depth: 0,
+            last_assignment_op: None,
+            warnings: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn parse_program(&mut self) -> Result<Vec<Stmt>, Vec<ParseError>> {
+        let mut statements = Vec::new();
+
+        while !self.is_at_end() {
+            match self.parse_declaration() {
+                Some(stmt) => statements.push(stmt),
+                None => self.synchronize(),
+            }
+        }
+
+        if self.errors.is_empty() {
+            Ok(statements)
+        } else {
+            Err(mem::take(&mut self.errors))
+        }
+    }
+
+    pub fn warnings(&self) -> &[ParseError] {
+        &self.warnings
+    }
+
+    pub fn parse(&mut self) -> Result<Expr, Vec<ParseError>> {
+        match self.parse_expression() {
+            Some(expr) if self.errors.is_empty() => Ok(expr),
+            _ => Err(mem::take(&mut self.errors)),
+        }
+    }
+
+    // Parses the tokens as one expression with nothing after it, such as a
+    // line typed at the REPL without a trailing ';'. Returns `None` if they
+    // are anything else, leaving `parse_program` to report the errors.
+    pub fn parse_lone_expression(&mut self) -> Option<Expr> {
+        let expr = self.parse_expression()?;
+
+        if self.errors.is_empty() && self.is_at_end() {
+            Some(expr)
+        } else {
+            None
+        }
+    }
+
+    fn parse_expression(&mut self) -> Option<Expr> {
+        self.parse_comma()
+    }
+
+    fn parse_comma(&mut self) -> Option<Expr> {
+        self.parse_binary_expr(Self::parse_assignment, vec![Comma])
+    }
+
+    fn parse_declaration(&mut self) -> Option<Stmt> {
+        if self.match_type(&[Class]) {
+            return self.parse_class_declaration();
+        }
+        // A leading `fun` without a name starts an expression statement
+        // holding an anonymous function instead.
+        if self.check(&Fun) && matches!(self.peek_next().typ, Identifier(_)) {
+            self.advance();
+            return Some(Stmt::FunctionStmt(Rc::new(
+                self.parse_function("function")?,
+            )));
+        }
+        if self.match_type(&[Var]) {
+            return self.parse_var_declaration();
+        }
+
+        self.parse_statement()
+    }
+
+    fn parse_class_declaration(&mut self) -> Option<Stmt> {
+        let name = self.consume_identifier("Expected class name.")?;
+
+        let superclass = if self.match_type(&[Less]) {
+            let superclass = self.consume_identifier("Expected superclass name.")?;
+            // Reported without giving up, since the class body still parses.
+            if superclass.lexeme == name.lexeme {
+                self.error(&superclass, "A class can't inherit from itself.");
+            }
+            Some(VariableExpr(superclass))
+        } else {
+            None
+        };
+
+        let enclosing_class = self.current_class;
+        self.current_class = if superclass.is_some() {
+            ClassType::Subclass
+        } else {
+            ClassType::Class
+        };
+        // A class declared inside a class method has its own `this`.
+        let enclosing_in_class_method = std::mem::replace(&mut self.in_class_method, false);
+        let body = self.parse_class_body();
+        self.current_class = enclosing_class;
+        self.in_class_method = enclosing_in_class_method;
+        let (methods, class_methods) = body?;
+
+        Some(Stmt::ClassStmt(ClassStmt {
+            name,
+            superclass,
+            methods,
+            class_methods,
+        }))
+    }
+
+    // Returns the instance methods and the class methods, in that order.
+    fn parse_class_body(&mut self) -> Option<(Methods, Methods)> {
+        self.consume(LeftBrace, "Expected '{' before class body.")?;
+
+        let mut methods = Vec::new();
+        let mut class_methods = Vec::new();
+        while !self.check(&RightBrace) && !self.is_at_end() {
+            if self.match_type(&[Class]) {
+                self.in_class_method = true;
+                let method = self.parse_method();
+                self.in_class_method = false;
+                class_methods.push(Rc::new(method?));
+            } else {
+                methods.push(Rc::new(self.parse_method()?));
+            }
+        }
+        self.consume(RightBrace, "Expected '}' after class body.")?;
+
+        Some((methods, class_methods))
+    }
+
+    // `kind` names what is being declared in error messages.
+    fn parse_function(&mut self, kind: &str) -> Option<FunctionStmt> {
+        let name = self.consume_identifier(&format!("Expected {} name.", kind))?;
+        self.consume(LeftParen, &format!("Expected '(' after {} name.", kind))?;
+
+        self.parse_function_rest(name, kind)
+    }
+
+    // Parses the parameters and body that follow a function's '('.
+    fn parse_function_rest(&mut self, name: Token, kind: &str) -> Option<FunctionStmt> {
+        let mut params = Vec::new();
+        if !self.check(&RightParen) {
+            loop {
+                if params.len() >= MAX_ARGUMENTS {
+                    self.error(
+                        self.peek(),
+                        &format!("Can't have more than {} parameters.", MAX_ARGUMENTS),
+                    );
+                }
+                params.push(self.consume_identifier("Expected parameter name.")?);
+                if !self.match_type(&[Comma]) || self.check(&RightParen) {
+                    break;
+                }
+            }
+        }
+        self.consume(RightParen, "Expected ')' after parameters.")?;
+        let body = self.parse_function_body(kind)?;
+
+        Some(FunctionStmt {
+            name,
+            params,
+            body,
+            getter: false,
+        })
+    }
+
+    fn parse_function_body(&mut self, kind: &str) -> Option<Vec<Stmt>> {
+        self.consume(LeftBrace, &format!("Expected '{{' before {} body.", kind))?;
+        // A function body can't break out of a loop surrounding the function.
+        let enclosing_loop_depth = std::mem::replace(&mut self.loop_depth, 0);
+        let body = self.parse_block();
+        self.loop_depth = enclosing_loop_depth;
+
+        body
+    }
+
+    // Like a function declaration, except that leaving out the parameter
+    // list makes the method a getter.
+    fn parse_method(&mut self) -> Option<FunctionStmt> {
+        let name = self.consume_identifier("Expected method name.")?;
+        if !self.check(&LeftBrace) {
+            self.consume(LeftParen, "Expected '(' after method name.")?;
+            return self.parse_function_rest(name, "method");
+        }
+
+        let body = self.parse_function_body("method")?;
+        Some(FunctionStmt {
+            name,
+            params: Vec::new(),
+            body,
+            getter: true,
+        })
+    }
+
+    fn parse_var_declaration(&mut self) -> Option<Stmt> {
+        let name = self.consume_identifier("Expected variable name.")?;
+
+        let initializer = if self.match_type(&[Equal]) {
+            Some(self.parse_expression()?)
+        } else {
+            None
+        };
+        self.consume(SemiColon, "Expected ';' after variable declaration.")?;
+
+        Some(Stmt::VarStmt(VarStmt { name, initializer }))
+    }
+
+    fn parse_statement(&mut self) -> Option<Stmt> {
+        if self.match_type(&[For]) {
+            return self.parse_for_statement();
+        }
+        if self.match_type(&[If]) {
+            return self.parse_if_statement();
+        }
+        if self.match_type(&[Print]) {
+            return self.parse_print_statement();
+        }
+        if self.match_type(&[Return]) {
+            return self.parse_return_statement();
+        }
+        if self.match_type(&[Switch]) {
+            return self.parse_switch_statement();
+        }
+        if self.match_type(&[While]) {
+            return self.parse_while_statement();
+        }
+        if self.match_type(&[Break, Continue]) {
+            return self.parse_loop_control_statement();
+        }
+        if self.match_type(&[LeftBrace]) {
+            return Some(Stmt::BlockStmt(BlockStmt(self.parse_block()?)));
+        }
+
+        self.parse_expression_statement()
+    }
+
+    fn parse_block(&mut self) -> Option<Vec<Stmt>> {
+        let mut statements = Vec::new();
+
+        while !self.check(&RightBrace) && !self.is_at_end() {
+            // Flags only the statement right after a `return`; everything
+            // after it is dead too, but one warning per block is enough.
+            if let Some(Stmt::ReturnStmt(_)) = statements.last() {
+                self.warn(self.peek(), "Unreachable code after return.");
+            }
+            statements.push(self.parse_declaration()?);
+        }
+        self.consume(RightBrace, "Expected '}' after block.")?;
+
+        Some(statements)
+    }
+
+    // There is no dedicated for node: the loop is desugared into a block
+    // holding the initializer and a while loop. The increment is stored on
+    // the `WhileStmt` and runs after every iteration, including ones that
+    // `continue`.
+    fn parse_for_statement(&mut self) -> Option<Stmt> {
+        self.consume(LeftParen, "Expected '(' after 'for'.")?;
+
+        let initializer = if self.match_type(&[SemiColon]) {
+            None
+        } else if self.match_type(&[Var]) {
+            Some(self.parse_var_declaration()?)
+        } else {
+            Some(self.parse_expression_statement()?)
+        };
+
+        let condition = if self.check(&SemiColon) {
+            let Token {
+                line,
+                column,
+                visual_column,
+                start,
+                ..
+            } = *self.peek();
+            Expr::LiteralExpr(LiteralExpr(Token::new(
+                True,
+                "true".into(),
+                line,
+                column,
+                visual_column,
+                start,
+                start,
+            )))
+        } else {
+            self.parse_expression()?
+        };
+        self.consume(SemiColon, "Expected ';' after loop condition.")?;
+
+        let increment = if self.check(&RightParen) {
+            None
+        } else {
+            Some(self.parse_expression()?)
+        };
+        self.consume(RightParen, "Expected ')' after for clauses.")?;
+
+        let body = self.parse_loop_body()?;
+
+        let mut body = Stmt::WhileStmt(WhileStmt {
+            condition,
+            body: Box::new(body),
+            increment,
+        });
+
+        if let Some(initializer) = initializer {
+            body = Stmt::BlockStmt(BlockStmt(vec![initializer, body]));
+        }
+
+        Some(body)
+    }
+
+    fn parse_if_statement(&mut self) -> Option<Stmt> {
+        let condition = self.parse_condition("if")?;
+
+        let then_branch = Box::new(self.parse_statement()?);
+        // Eagerly claiming the "else" binds it to the nearest "if".
+        let else_branch = if self.match_type(&[Else]) {
+            Some(Box::new(self.parse_statement()?))
+        } else {
+            None
+        };
+
+        Some(Stmt::IfStmt(IfStmt {
+            condition,
+            then_branch,
+            else_branch,
+        }))
+    }
+
+    fn parse_switch_statement(&mut self) -> Option<Stmt> {
+        self.consume(LeftParen, "Expected '(' after 'switch'.")?;
+        let discriminant = self.parse_expression()?;
+        self.consume(RightParen, "Expected ')' after switch value.")?;
+        self.consume(LeftBrace, "Expected '{' before switch body.")?;
+
+        let mut cases = Vec::new();
+        let mut default = None;
+        while !self.check(&RightBrace) && !self.is_at_end() {
+            if self.match_type(&[Case]) {
+                let value = self.parse_expression()?;
+                self.consume(Colon, "Expected ':' after case value.")?;
+                let body = self.parse_case_body()?;
+                cases.push(SwitchCase { value, body });
+            } else if self.match_type(&[Default]) {
+                let keyword = self.previous();
+                // Reported without giving up, keeping the first default.
+                if default.is_some() {
+                    self.error(keyword, "A switch can only have one default.");
+                }
+                self.consume(Colon, "Expected ':' after 'default'.")?;
+                let body = self.parse_case_body()?;
+                default.get_or_insert(body);
+            } else {
+                self.error(self.peek(), "Expected 'case' or 'default'.");
+                return None;
+            }
+        }
+        self.consume(RightBrace, "Expected '}' after switch body.")?;
+
+        Some(Stmt::SwitchStmt(SwitchStmt {
+            discriminant,
+            cases,
+            default,
+        }))
+    }
+
+    // A case's statements run up to the next label or the end of the switch.
+    fn parse_case_body(&mut self) -> Option<Vec<Stmt>> {
+        let mut statements = Vec::new();
+        while !self.check(&Case)
+            && !self.check(&Default)
+            && !self.check(&RightBrace)
+            && !self.is_at_end()
+        {
+            statements.push(self.parse_declaration()?);
+        }
+
+        Some(statements)
+    }
+
+    fn parse_while_statement(&mut self) -> Option<Stmt> {
+        let condition = self.parse_condition("while")?;
+        let body = Box::new(self.parse_loop_body()?);
+
+        Some(Stmt::WhileStmt(WhileStmt {
+            condition,
+            body,
+            increment: None,
+        }))
+    }
+
+    // Parses the parenthesized condition of an `if` or `while`. Assigning
+    // there with `=` is legal but usually a typo for `==`, so it's flagged
+    // unless wrapped in a second pair of parentheses.
+    fn parse_condition(&mut self, keyword: &str) -> Option<Expr> {
+        self.consume(LeftParen, &format!("Expected '(' after '{}'.", keyword))?;
+        let condition = self.parse_expression()?;
+        self.consume(
+            RightParen,
+            &format!("Expected ')' after {} condition.", keyword),
+        )?;
+
+        // Compound assignments are also `AssignExpr`s, but can't be a
+        // mistyped `==`.
+        if let (Expr::AssignExpr(_) | Expr::SetExpr(_), Some(op)) =
+            (&condition, self.last_assignment_op)
+        {
+            if op.typ == Equal {
+                self.warn(op, "Assignment used as a condition; did you mean '=='?");
+            }
+        }
+
+        Some(condition)
+    }
+
+    fn parse_loop_body(&mut self) -> Option<Stmt> {
+        self.loop_depth += 1;
+        let body = self.parse_statement();
+        self.loop_depth -= 1;
+
+        body
+    }
+
+    fn parse_loop_control_statement(&mut self) -> Option<Stmt> {
+        let keyword = self.previous().clone();
+        if self.loop_depth == 0 {
+            self.error(
+                &keyword,
+                &format!("Can't use '{}' outside of a loop.", keyword.lexeme),
+            );
+            return None;
+        }
+        self.consume(
+            SemiColon,
+            &format!("Expected ';' after '{}'.", keyword.lexeme),
+        )?;
+
+        match keyword.typ {
+            Break => Some(Stmt::BreakStmt(BreakStmt(keyword))),
+            _ => Some(Stmt::ContinueStmt(ContinueStmt(keyword))),
         }
     }
 
-    pub fn parse(&mut self) -> Option<Expr> {
-        self.parse_equality()
+    fn parse_print_statement(&mut self) -> Option<Stmt> {
+        let expr = self.parse_expression()?;
+        self.consume(SemiColon, "Expected ';' after value.")?;
+
+        Some(Stmt::PrintStmt(PrintStmt(expr)))
+    }
+
+    fn parse_return_statement(&mut self) -> Option<Stmt> {
+        let keyword = self.previous().clone();
+        let value = if self.check(&SemiColon) {
+            None
+        } else {
+            Some(self.parse_expression()?)
+        };
+        self.consume(SemiColon, "Expected ';' after return value.")?;
+
+        Some(Stmt::ReturnStmt(ReturnStmt { keyword, value }))
+    }
+
+    fn parse_expression_statement(&mut self) -> Option<Stmt> {
+        let expr = self.parse_expression()?;
+        self.consume(SemiColon, "Expected ';' after expression.")?;
+
+        Some(Stmt::ExpressionStmt(ExpressionStmt(expr)))
     }
 
     fn parse_binary_expr<F>(&mut self, parse_next: F, token_types: Vec<TokenType>) -> Option<Expr>
     where
         F: Fn(&mut Parser<'a>) -> Option<Expr>,
     {
-        let mut left = match parse_next(self) {
-            Some(e) => e,
-            None => return None,
-        };
+        let mut left = parse_next(self)?;
 
         while self.match_type(&token_types) {
             let op = match &self.previous().typ {
                 t if token_types.contains(t) => match t {
-                    EqualEqual | BangEqual | Less | LessEqual | Greater | GreaterEqual | Minus
-                    | Plus | Slash | Star => self.previous().clone(),
+                    Comma | EqualEqual | BangEqual | Less | LessEqual | Greater | GreaterEqual
+                    | Minus | Plus | Slash | Star | Percent | StarStar | TildeSlash => {
+                        self.previous().clone()
+                    }
                     _ => {
-                        self.error(self.peek().clone(), "Expected a binary operator.");
+                        self.error(self.peek(), "Expected a binary operator.");
                         return None;
                     }
                 },
                 _ => unreachable!(),
             };
-            let right = match parse_next(self) {
-                Some(e) => e,
-                None => return None,
-            };
+            let right = parse_next(self)?;
             let expr = Expr::BinaryExpr(BinaryExpr {
                 left: Box::new(left),
                 op,
@@ -75,6 +627,133 @@ impl<'a> Parser<'a> {
         Some(left)
     }
 
+    fn parse_assignment(&mut self) -> Option<Expr> {
+        let expr = self.parse_ternary()?;
+
+        if self.match_type(&[Equal]) {
+            let equals = self.previous();
+            // Assignment is right-associative.
+            let value = self.parse_assignment()?;
+
+            match expr {
+                Expr::VariableExpr(VariableExpr(name)) => {
+                    self.last_assignment_op = Some(equals);
+                    return Some(Expr::AssignExpr(AssignExpr {
+                        name,
+                        value: Box::new(value),
+                    }));
+                }
+                Expr::GetExpr(GetExpr { object, name }) => {
+                    self.last_assignment_op = Some(equals);
+                    return Some(Expr::SetExpr(SetExpr {
+                        object,
+                        name,
+                        value: Box::new(value),
+                    }));
+                }
+                _ => (),
+            }
+
+            self.error(equals, "Invalid assignment target.");
+            return None;
+        }
+
+        if self.match_type(&[PlusEqual, MinusEqual, StarEqual, SlashEqual]) {
+            let compound = self.previous();
+            let value = self.parse_assignment()?;
+
+            let Expr::VariableExpr(VariableExpr(name)) = expr else {
+                self.error(compound, "Invalid compound assignment target.");
+                return None;
+            };
+
+            self.last_assignment_op = Some(compound);
+            // `x += e` desugars to `x = x + e`. The target is a plain
+            // variable, so reading it again has no side effects.
+            let (typ, lexeme) = match compound.typ {
+                PlusEqual => (Plus, "+"),
+                MinusEqual => (Minus, "-"),
+                StarEqual => (Star, "*"),
+                SlashEqual => (Slash, "/"),
+                _ => unreachable!(),
+            };
+            let op = Token::new(
+                typ,
+                lexeme.to_string(),
+                compound.line,
+                compound.column,
+                compound.visual_column,
+                compound.start,
+                compound.end,
+            );
+            return Some(Expr::AssignExpr(AssignExpr {
+                name: name.clone(),
+                value: Box::new(Expr::BinaryExpr(BinaryExpr {
+                    left: Box::new(Expr::VariableExpr(VariableExpr(name))),
+                    op,
+                    right: Box::new(value),
+                })),
+            }));
+        }
+
+        Some(expr)
+    }
+
+    fn parse_logical_expr<F>(&mut self, parse_next: F, token_types: &[TokenType]) -> Option<Expr>
+    where
+        F: Fn(&mut Parser<'a>) -> Option<Expr>,
+    {
+        let mut left = parse_next(self)?;
+
+        while self.match_type(token_types) {
+            let op = self.previous().clone();
+            let right = parse_next(self)?;
+            left = Expr::LogicalExpr(LogicalExpr {
+                left: Box::new(left),
+                op,
+                right: Box::new(right),
+            });
+        }
+
+        Some(left)
+    }
+
+    fn parse_ternary(&mut self) -> Option<Expr> {
+        let condition = self.parse_coalesce()?;
+
+        if !self.match_type(&[Question]) {
+            return Some(condition);
+        }
+        let line = self.previous().line;
+
+        let then_expr = self.parse_expression()?;
+        self.consume(
+            Colon,
+            "Expected ':' after then branch of conditional expression.",
+        )?;
+        // The else branch recurses so that `a ? b : c ? d : e` nests to the right.
+        let else_expr = self.parse_ternary()?;
+
+        Some(Expr::TernaryExpr(TernaryExpr {
+            condition: Box::new(condition),
+            then_expr: Box::new(then_expr),
+            else_expr: Box::new(else_expr),
+            line,
+        }))
+    }
+
+    fn parse_coalesce(&mut self) -> Option<Expr> {
+        self.parse_logical_expr(Self::parse_or, &[QuestionQuestion])
+    }
+
+    fn parse_or(&mut self) -> Option<Expr> {
+        self.parse_logical_expr(Self::parse_and, &[Or])
+    }
+
+    fn parse_and(&mut self) -> Option<Expr> {
+        self.parse_logical_expr(Self::parse_equality, &[And])
+    }
+
     fn parse_equality(&mut self) -> Option<Expr> {
         self.parse_binary_expr(Self::parse_comparison, vec![EqualEqual, BangEqual])
     }
@@ -91,31 +770,130 @@ impl<'a> Parser<'a> {
     }
 
     fn parse_factor(&mut self) -> Option<Expr> {
-        self.parse_binary_expr(Self::parse_unary, vec![Slash, Star])
+        self.parse_binary_expr(Self::parse_unary, vec![Slash, Star, Percent, TildeSlash])
     }
 
     fn parse_unary(&mut self) -> Option<Expr> {
+        if self.match_type(&[PlusPlus, MinusMinus]) {
+            let op = self.previous().clone();
+            let operand = self.parse_unary()?;
+            return self.update_expr(op, operand, true);
+        }
+
         if self.match_type(&[Bang, Minus]) {
             let op = match self.previous().typ {
                 Bang | Minus => self.previous().clone(),
                 _ => unreachable!(),
             };
-            let expr = match self.parse_unary() {
-                Some(e) => e,
-                None => return None,
-            };
+            let expr = self.parse_unary()?;
             return Some(Expr::UnaryExpr(UnaryExpr {
                 op,
                 expr: Box::new(expr),
             }));
         }
 
-        self.parse_primary()
+        self.parse_power()
+    }
+
+    // Exponentiation binds tighter than unary minus, so `-2 ** 2` is -4, and
+    // is right-associative because its right operand recurses back through
+    // `unary`: `2 ** 3 ** 2` is `2 ** (3 ** 2)`.
+    fn parse_power(&mut self) -> Option<Expr> {
+        let base = self.parse_call()?;
+
+        if self.match_type(&[StarStar]) {
+            let op = self.previous().clone();
+            let exponent = self.parse_unary()?;
+            return Some(Expr::BinaryExpr(BinaryExpr {
+                left: Box::new(base),
+                op,
+                right: Box::new(exponent),
+            }));
+        }
+
+        Some(base)
+    }
+
+    fn parse_call(&mut self) -> Option<Expr> {
+        let mut expr = self.parse_primary()?;
+
+        loop {
+            if self.match_type(&[LeftParen]) {
+                expr = self.finish_call(expr)?;
+            } else if self.match_type(&[Dot]) {
+                let name = self.consume_identifier("Expected property name after '.'.")?;
+                expr = Expr::GetExpr(GetExpr {
+                    object: Box::new(expr),
+                    name,
+                });
+            } else if self.match_type(&[LeftBracket]) {
+                let index = self.parse_expression()?;
+                let bracket = self
+                    .consume(RightBracket, "Expected ']' after index.")?
+                    .clone();
+                expr = Expr::IndexExpr(IndexExpr {
+                    object: Box::new(expr),
+                    bracket,
+                    index: Box::new(index),
+                });
+            } else {
+                break;
+            }
+        }
+
+        if self.match_type(&[PlusPlus, MinusMinus]) {
+            let op = self.previous().clone();
+            return self.update_expr(op, expr, false);
+        }
+
+        Some(expr)
+    }
+
+    // Only variables can be incremented or decremented, since the update
+    // needs somewhere to store the result.
+    fn update_expr(&mut self, op: Token, operand: Expr, prefix: bool) -> Option<Expr> {
+        let Expr::VariableExpr(VariableExpr(name)) = operand else {
+            self.error(&op, "Invalid increment or decrement target.");
+            return None;
+        };
+
+        Some(Expr::UpdateExpr(UpdateExpr { name, op, prefix }))
+    }
+
+    fn finish_call(&mut self, callee: Expr) -> Option<Expr> {
+        let mut args = Vec::new();
+        if !self.check(&RightParen) {
+            loop {
+                // Reported without bailing out; the call itself still parses.
+                if args.len() >= MAX_ARGUMENTS {
+                    self.error(
+                        self.peek(),
+                        &format!("Can't have more than {} arguments.", MAX_ARGUMENTS),
+                    );
+                }
+                // Arguments sit above the comma operator so that commas
+                // separate them instead of joining them.
+                args.push(self.parse_assignment()?);
+                // A trailing comma is allowed, but only one.
+                if !self.match_type(&[Comma]) || self.check(&RightParen) {
+                    break;
+                }
+            }
+        }
+        let paren = self
+            .consume(RightParen, "Expected ')' after arguments.")?
+            .clone();
+
+        Some(Expr::CallExpr(CallExpr {
+            callee: Box::new(callee),
+            paren,
+            args,
+        }))
     }
 
     fn parse_primary(&mut self) -> Option<Expr> {
         if self.peek().typ == EOF {
-            self.error(self.peek().clone(), "Expected a literal or '('.");
+            self.error(self.peek(), "Expected a literal or '('.");
             return None;
         }
         let token = self.advance();
@@ -123,35 +901,152 @@ impl<'a> Parser<'a> {
             Number(_) | String(_) | True | False | Nil => {
                 Some(Expr::LiteralExpr(LiteralExpr(token.clone())))
             }
+            Identifier(_) => Some(Expr::VariableExpr(VariableExpr(token.clone()))),
+            This => {
+                // Reported without giving up, since the rest of the
+                // expression still parses fine.
+                if self.in_class_method {
+                    self.error(token, "Can't use 'this' in a class method.");
+                }
+                Some(Expr::ThisExpr(ThisExpr(token.clone())))
+            }
+            Fun => {
+                if let Identifier(_) = self.peek().typ {
+                    self.error(self.peek(), "Named functions can't be used as expressions.");
+                    return None;
+                }
+                let name = Token {
+                    lexeme: "".to_string(),
+                    ..token.clone()
+                };
+                self.consume(LeftParen, "Expected '(' after 'fun'.")?;
+
+                let function = self.parse_function_rest(name, "function")?;
+                Some(Expr::FunctionExpr(FunctionExpr(Rc::new(function))))
+            }
+            Super => {
+                let keyword = token.clone();
+                // Like `this`, misplaced uses are reported without giving up
+                // on the rest of the expression.
+                match self.current_class {
+                    _ if self.in_class_method => {
+                        self.error(&keyword, "Can't use 'super' in a class method.");
+                    }
+                    ClassType::Subclass => (),
+                    ClassType::Class => {
+                        self.error(&keyword, "Can't use 'super' in a class with no superclass.");
+                    }
+                    ClassType::None => {
+                        self.error(&keyword, "Can't use 'super' outside of a class.");
+                    }
+                }
+
+                self.consume(Dot, "Expected '.' after 'super'.")?;
+                let method = self.consume_identifier("Expected superclass method name.")?;
+
+                Some(Expr::SuperExpr(SuperExpr { keyword, method }))
+            }
             LeftParen => {
-                let expr = match self.parse() {
+                let line = self.previous().line;
+                let expr = match self.parse_expression() {
                     Some(e) => e,
                     None => {
-                        self.error(self.peek().clone(), "Expected an expression.");
+                        self.error(self.peek(), "Expected an expression.");
                         return None;
                     }
                 };
                 if self.peek().typ != RightParen {
-                    self.error(self.peek().clone(), "Expected ')'.");
+                    self.error(self.peek(), "Expected ')'.");
                     return None;
                 }
 
                 // Consume ")"
                 self.advance();
 
-                Some(expr)
+                Some(Expr::GroupingExpr(GroupingExpr {
+                    expr: Box::new(expr),
+                    line,
+                }))
+            }
+            LeftBracket => {
+                let bracket = token.clone();
+                let mut elements = Vec::new();
+                if !self.check(&RightBracket) {
+                    loop {
+                        // Like call arguments, elements sit above the comma
+                        // operator.
+                        elements.push(self.parse_assignment()?);
+                        if !self.match_type(&[Comma]) {
+                            break;
+                        }
+                    }
+                }
+                self.consume(RightBracket, "Expected ']' after list elements.")?;
+
+                Some(Expr::ListExpr(ListExpr { bracket, elements }))
+            }
+            EqualEqual | BangEqual => self.parse_missing_left_operand(Self::parse_comparison),
+            Less | LessEqual | Greater | GreaterEqual => {
+                self.parse_missing_left_operand(Self::parse_term)
+            }
+            Plus => self.parse_missing_left_operand(Self::parse_factor),
+            Slash | Star | Percent | StarStar | TildeSlash => {
+                self.parse_missing_left_operand(Self::parse_unary)
             }
             _ => {
-                self.error(self.previous().clone(), "Expected a literal or '('.");
+                self.error(self.previous(), "Expected a literal or '('.");
                 None
             }
         }
     }
 
-    fn peek(&self) -> &Token {
+    // Reports a binary operator that appears without a left-hand operand, then
+    // parses and discards its right operand so the error doesn't cascade.
+    fn parse_missing_left_operand(
+        &mut self,
+        parse_right: fn(&mut Self) -> Option<Expr>,
+    ) -> Option<Expr> {
+        let op = self.previous();
+        self.error(
+            op,
+            format!(
+                "Binary operator '{}' requires a left-hand operand.",
+                op.lexeme
+            )
+            .as_str(),
+        );
+        parse_right(self);
+        None
+    }
+
+    fn consume(&mut self, typ: TokenType, message: &str) -> Option<&Token> {
+        if self.check(&typ) {
+            return Some(self.advance());
+        }
+
+        self.error(self.peek(), message);
+        None
+    }
+
+    fn consume_identifier(&mut self, message: &str) -> Option<Token> {
+        if let Identifier(_) = self.peek().typ {
+            return Some(self.advance().clone());
+        }
+
+        self.error(self.peek(), message);
+        None
+    }
+
+    fn peek(&self) -> &'a Token {
         &self.tokens[self.curr]
     }
 
+    // The token after `peek`, or the final EOF if there is none.
+    fn peek_next(&self) -> &'a Token {
+        let last = self.tokens.len() - 1;
+        &self.tokens[(self.curr + 1).min(last)]
+    }
+
     fn match_type(&mut self, token_types: &[TokenType]) -> bool {
         for typ in token_types {
             if self.check(typ) {
@@ -163,7 +1058,7 @@ impl<'a> Parser<'a> {
         false
     }
 
-    fn advance(&mut self) -> &Token {
+    fn advance(&mut self) -> &'a Token {
         if !self.is_at_end() {
             self.curr += 1;
         }
@@ -177,7 +1072,7 @@ impl<'a> Parser<'a> {
         self.peek().typ == *typ
     }
 
-    fn previous(&self) -> &Token {
+    fn previous(&self) -> &'a Token {
         &self.tokens[self.curr - 1]
     }
 
@@ -185,12 +1080,32 @@ impl<'a> Parser<'a> {
         self.peek().typ == TokenType::EOF
     }
 
-    fn error(&mut self, token: Token, message: &str) -> Option<()> {
-        self.error_reporter
-            .parse_error(&token, message, exitcode::DATAERR);
+    fn error(&mut self, token: &Token, message: &str) -> Option<()> {
+        self.errors.push(ParseError {
+            line: token.line,
+            column: token.column,
+            visual_column: token.visual_column,
+            lexeme: match token.typ {
+                EOF => None,
+                _ => Some(token.lexeme.clone()),
+            },
+            message: message.to_string(),
+            exit_code: exitcode::DATAERR,
+        });
         None
     }
 
+    fn warn(&mut self, token: &Token, message: &str) {
+        self.warnings.push(ParseError {
+            line: token.line,
+            column: token.column,
+            visual_column: token.visual_column,
+            lexeme: Some(token.lexeme.clone()),
+            message: message.to_string(),
+            exit_code: exitcode::OK,
+        });
+    }
+
     fn synchronize(&mut self) {
         self.advance();
 
@@ -200,7 +1115,7 @@ impl<'a> Parser<'a> {
             }
 
             match self.peek().typ {
-                Class | Fun | Var | For | If | While | Print | Return => return,
+                Class | Fun | Var | For | If | While | Print | Return | Switch => return,
                 _ => (),
             }
 
@@ -212,12 +1127,17 @@ impl<'a> Parser<'a> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{error_reporter::ErrorReporter, scanner::Scanner};
 
     #[test]
     fn parse_empty() {
         let expr = get_expr(vec![Token {
             lexeme: "".to_string(),
             line: 1,
+            column: 1,
+            visual_column: 1,
+            start: 0,
+            end: 0,
             typ: EOF,
         }]);
 
@@ -230,26 +1150,46 @@ mod tests {
             Token {
                 lexeme: "(".to_string(),
                 line: 1,
+                column: 1,
+                visual_column: 1,
+                start: 0,
+                end: 1,
                 typ: LeftParen,
             },
             Token {
                 lexeme: "1".to_string(),
                 line: 1,
+                column: 2,
+                visual_column: 2,
+                start: 1,
+                end: 2,
                 typ: Number(1.0),
             },
             Token {
                 lexeme: "+".to_string(),
                 line: 1,
+                column: 3,
+                visual_column: 3,
+                start: 2,
+                end: 3,
                 typ: Plus,
             },
             Token {
                 lexeme: "2".to_string(),
                 line: 1,
+                column: 4,
+                visual_column: 4,
+                start: 3,
+                end: 4,
                 typ: Number(2.0),
             },
             Token {
                 lexeme: "".to_string(),
                 line: 1,
+                column: 5,
+                visual_column: 5,
+                start: 4,
+                end: 4,
                 typ: EOF,
             },
         ]);
@@ -257,9 +1197,900 @@ mod tests {
         assert_eq!(expr, None);
     }
 
-    fn get_expr(tokens: Vec<Token>) -> Option<Expr> {
+    #[test]
+    fn parse_grouping() {
+        let one = Token {
+            lexeme: "1".to_string(),
+            line: 1,
+            column: 2,
+            visual_column: 2,
+            start: 1,
+            end: 2,
+            typ: Number(1.0),
+        };
+        let expr = get_expr(vec![
+            Token {
+                lexeme: "(".to_string(),
+                line: 1,
+                column: 1,
+                visual_column: 1,
+                start: 0,
+                end: 1,
+                typ: LeftParen,
+            },
+            one.clone(),
+            Token {
+                lexeme: ")".to_string(),
+                line: 1,
+                column: 3,
+                visual_column: 3,
+                start: 2,
+                end: 3,
+                typ: RightParen,
+            },
+            Token {
+                lexeme: "".to_string(),
+                line: 1,
+                column: 4,
+                visual_column: 4,
+                start: 3,
+                end: 3,
+                typ: EOF,
+            },
+        ]);
+
+        assert_eq!(
+            expr,
+            Some(Expr::GroupingExpr(GroupingExpr {
+                expr: Box::new(Expr::LiteralExpr(LiteralExpr(one))),
+                line: 1,
+            }))
+        );
+    }
+
+    #[test]
+    fn parse_print_statement() {
+        let stmts = get_stmts("print 1;");
+
+        assert!(matches!(
+            stmts.as_deref(),
+            Some([Stmt::PrintStmt(PrintStmt(Expr::LiteralExpr(_)))])
+        ));
+    }
+
+    #[test]
+    fn parse_expression_statement() {
+        let stmts = get_stmts("1 + 2;");
+
+        assert!(matches!(
+            stmts.as_deref(),
+            Some([Stmt::ExpressionStmt(ExpressionStmt(Expr::BinaryExpr(_)))])
+        ));
+    }
+
+    #[test]
+    fn parse_var_declaration() {
+        let stmts = get_stmts("var x = 1; var y;");
+
+        assert!(matches!(
+            stmts.as_deref(),
+            Some([
+                Stmt::VarStmt(VarStmt {
+                    initializer: Some(Expr::LiteralExpr(_)),
+                    ..
+                }),
+                Stmt::VarStmt(VarStmt {
+                    initializer: None,
+                    ..
+                }),
+            ])
+        ));
+    }
+
+    #[test]
+    fn parse_chained_assignment() {
+        let stmts = get_stmts("x = y = 3;");
+
+        let Some([Stmt::ExpressionStmt(ExpressionStmt(Expr::AssignExpr(outer)))]) =
+            stmts.as_deref()
+        else {
+            panic!("expected an assignment statement, got {:?}", stmts);
+        };
+        assert_eq!(outer.name.lexeme, "x");
+        assert!(matches!(
+            outer.value.as_ref(),
+            Expr::AssignExpr(AssignExpr { name, .. }) if name.lexeme == "y"
+        ));
+    }
+
+    #[test]
+    fn parse_invalid_assignment_target() {
+        let mut error_reporter = ErrorReporter::default();
+        let tokens = Scanner::new("1 = 2;".to_string(), &mut error_reporter).scan_tokens();
+        let stmts = Parser::new(&tokens).parse_program();
+
+        assert!(stmts.is_err());
+    }
+
+    #[test]
+    fn parse_missing_left_operand_for_plus() {
+        let mut error_reporter = ErrorReporter::default();
+        let tokens = Scanner::new("+ 1;".to_string(), &mut error_reporter).scan_tokens();
+        let errors = Parser::new(&tokens).parse_program().unwrap_err();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].message,
+            "Binary operator '+' requires a left-hand operand."
+        );
+        assert_eq!(
+            (
+                errors[0].line,
+                errors[0].column,
+                errors[0].lexeme.as_deref()
+            ),
+            (1, 1, Some("+"))
+        );
+    }
+
+    #[test]
+    fn parse_missing_left_operand_for_less_equal() {
+        let mut error_reporter = ErrorReporter::default();
+        let tokens =
+            Scanner::new("print 1;\n<= 2; print 3;".to_string(), &mut error_reporter).scan_tokens();
+        let errors = Parser::new(&tokens).parse_program().unwrap_err();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].message,
+            "Binary operator '<=' requires a left-hand operand."
+        );
+        assert_eq!(
+            (
+                errors[0].line,
+                errors[0].column,
+                errors[0].lexeme.as_deref()
+            ),
+            (2, 1, Some("<="))
+        );
+    }
+
+    #[test]
+    fn parse_reports_multiple_errors() {
+        let mut error_reporter = ErrorReporter::default();
+        let source = "var = 1;\nprint 2;\nprint (3;\nprint 4;";
+        let tokens = Scanner::new(source.to_string(), &mut error_reporter).scan_tokens();
+        let errors = Parser::new(&tokens).parse_program().unwrap_err();
+
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn parse_block() {
+        let stmts = get_stmts("{ var x = 1; print x; }");
+
+        assert!(matches!(
+            stmts.as_deref(),
+            Some([Stmt::BlockStmt(BlockStmt(inner))]) if inner.len() == 2
+        ));
+    }
+
+    fn get_warnings(source: &str) -> Vec<std::string::String> {
+        let mut error_reporter = ErrorReporter::default();
+        let tokens = Scanner::new(source.to_string(), &mut error_reporter).scan_tokens();
+        let mut parser = Parser::new(&tokens);
+        parser.parse_program().unwrap();
+
+        parser
+            .warnings()
+            .iter()
+            .map(|warning| warning.message.clone())
+            .collect()
+    }
+
+    #[test]
+    fn parse_warns_on_assignment_as_condition() {
+        let source = "var x;\nif (x = 1) print x;";
+        let mut error_reporter = ErrorReporter::default();
+        let tokens = Scanner::new(source.to_string(), &mut error_reporter).scan_tokens();
+        let mut parser = Parser::new(&tokens);
+
+        assert!(parser.parse_program().is_ok());
+        let warnings = parser.warnings();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].message,
+            "Assignment used as a condition; did you mean '=='?"
+        );
+        assert_eq!((warnings[0].line, warnings[0].column), (2, 7));
+        assert_eq!(get_warnings("while (x = false) {}").len(), 1);
+    }
+
+    #[test]
+    fn parse_allows_other_conditions() {
+        assert!(get_warnings("if (x == 1) print x;").is_empty());
+        assert!(get_warnings("while ((x = next())) {}").is_empty());
+        assert!(get_warnings("while (x += 1) {}").is_empty());
+    }
+
+    #[test]
+    fn parse_warns_on_property_assignment_as_condition() {
+        let mut error_reporter = ErrorReporter::default();
+        let tokens = Scanner::new("if (a.b = 1) {}".to_string(), &mut error_reporter).scan_tokens();
+        let mut parser = Parser::new(&tokens);
+
+        assert!(parser.parse_program().is_ok());
+        let warnings = parser.warnings();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(
+            warnings[0].message,
+            "Assignment used as a condition; did you mean '=='?"
+        );
+        assert_eq!((warnings[0].line, warnings[0].column), (1, 9));
+        assert_eq!(get_warnings("while (x += (y = 1)) {}").len(), 0);
+    }
+
+    #[test]
+    fn parse_unreachable_code_after_return() {
+        let mut error_reporter = ErrorReporter::default();
+        let source = "fun f() {\n  return 1;\n  print 2;\n  print 3;\n}";
+        let tokens = Scanner::new(source.to_string(), &mut error_reporter).scan_tokens();
+        let mut parser = Parser::new(&tokens);
+
+        assert!(parser.parse_program().is_ok());
+        let warnings = parser.warnings();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].message, "Unreachable code after return.");
+        assert_eq!((warnings[0].line, warnings[0].column), (3, 3));
+    }
+
+    #[test]
+    fn parse_unterminated_block() {
+        assert_eq!(get_stmts("{ print 1;"), None);
+    }
+
+    #[test]
+    fn parse_dangling_else() {
+        let stmts = get_stmts("if (a) if (b) print 1; else print 2;");
+
+        let Some([Stmt::IfStmt(outer)]) = stmts.as_deref() else {
+            panic!("expected an if statement, got {:?}", stmts);
+        };
+        assert_eq!(outer.else_branch, None);
+        assert!(matches!(
+            outer.then_branch.as_ref(),
+            Stmt::IfStmt(IfStmt {
+                else_branch: Some(_),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn parse_while() {
+        let stmts = get_stmts("while (x) { x = x - 1; }");
+
+        assert!(matches!(
+            stmts.as_deref(),
+            Some([Stmt::WhileStmt(WhileStmt {
+                condition: Expr::VariableExpr(_),
+                body,
+                increment: None,
+            })]) if matches!(body.as_ref(), Stmt::BlockStmt(_))
+        ));
+    }
+
+    #[test]
+    fn parse_switch() {
+        let stmts = get_stmts("switch (x) { case 1: print 1; print 2; case 2: default: print 3; }");
+
+        let Some(
+            [Stmt::SwitchStmt(SwitchStmt {
+                discriminant: Expr::VariableExpr(_),
+                cases,
+                default: Some(default),
+            })],
+        ) = stmts.as_deref()
+        else {
+            panic!("expected a switch, got {:?}", stmts);
+        };
+        assert!(matches!(
+            cases.as_slice(),
+            [SwitchCase { body: first, .. }, SwitchCase { body: second, .. }]
+                if first.len() == 2 && second.is_empty()
+        ));
+        assert!(matches!(default.as_slice(), [Stmt::PrintStmt(_)]));
+    }
+
+    #[test]
+    fn parse_switch_errors() {
+        assert_eq!(get_stmts("switch (x) { print 1; }"), None);
+        assert_eq!(get_stmts("switch (x) { case 1 print 1; }"), None);
+        assert_eq!(get_stmts("switch (x) { default: default: }"), None);
+        assert_eq!(get_stmts("switch (x) { case 1: print 1;"), None);
+        assert_eq!(
+            get_errors("switch (x) {\n  default: print 1;\n  default: print 2;\n}"),
+            vec![(3, "A switch can only have one default.".to_string())]
+        );
+    }
+
+    #[test]
+    fn parse_for_desugars_to_while() {
+        let stmts = get_stmts("for (var i = 0; i < 3; i = i + 1) print i;");
+
+        // Expected: { var i = 0; while (i < 3) print i; } with `i = i + 1`
+        // kept as the loop's increment.
+        let Some([Stmt::BlockStmt(BlockStmt(outer))]) = stmts.as_deref() else {
+            panic!("expected a block, got {:?}", stmts);
+        };
+        let [Stmt::VarStmt(_), Stmt::WhileStmt(WhileStmt {
+            condition: Expr::BinaryExpr(BinaryExpr { op, .. }),
+            body,
+            increment: Some(Expr::AssignExpr(_)),
+        })] = outer.as_slice()
+        else {
+            panic!("expected a declaration and a while loop, got {:?}", outer);
+        };
+        assert_eq!(op.typ, Less);
+        assert!(matches!(body.as_ref(), Stmt::PrintStmt(_)));
+    }
+
+    #[test]
+    fn parse_for_without_clauses() {
+        let stmts = get_stmts("for (;;) print 1;");
+
+        let Some(
+            [Stmt::WhileStmt(WhileStmt {
+                condition: Expr::LiteralExpr(LiteralExpr(token)),
+                body,
+                increment: None,
+            })],
+        ) = stmts.as_deref()
+        else {
+            panic!("expected a bare while loop, got {:?}", stmts);
+        };
+        assert_eq!(token.typ, True);
+        assert!(matches!(body.as_ref(), Stmt::PrintStmt(_)));
+    }
+
+    #[test]
+    fn parse_break_and_continue_in_loop() {
+        let stmts = get_stmts("while (true) { continue; break; }");
+
+        let Some([Stmt::WhileStmt(WhileStmt { body, .. })]) = stmts.as_deref() else {
+            panic!("expected a while loop, got {:?}", stmts);
+        };
+        assert!(matches!(
+            body.as_ref(),
+            Stmt::BlockStmt(BlockStmt(inner))
+                if matches!(inner.as_slice(), [Stmt::ContinueStmt(_), Stmt::BreakStmt(_)])
+        ));
+    }
+
+    #[test]
+    fn parse_break_outside_loop() {
+        assert_eq!(get_stmts("break;"), None);
+        assert_eq!(get_stmts("if (true) continue;"), None);
+    }
+
+    #[test]
+    fn parse_break_in_function_inside_loop() {
+        assert_eq!(get_stmts("while (true) { fun f() { break; } }"), None);
+    }
+
+    #[test]
+    fn parse_and_binds_tighter_than_or() {
+        let stmts = get_stmts("a or b and c;");
+
+        let Some([Stmt::ExpressionStmt(ExpressionStmt(Expr::LogicalExpr(or)))]) = stmts.as_deref()
+        else {
+            panic!("expected a logical expression, got {:?}", stmts);
+        };
+        assert_eq!(or.op.typ, Or);
+        assert!(matches!(
+            or.right.as_ref(),
+            Expr::LogicalExpr(LogicalExpr { op, .. }) if op.typ == And
+        ));
+    }
+
+    #[test]
+    fn parse_coalesce_binds_looser_than_or() {
+        let stmts = get_stmts("a ?? b or c;");
+
+        let Some([Stmt::ExpressionStmt(ExpressionStmt(Expr::LogicalExpr(coalesce)))]) =
+            stmts.as_deref()
+        else {
+            panic!("expected a logical expression, got {:?}", stmts);
+        };
+        assert_eq!(coalesce.op.typ, QuestionQuestion);
+        assert!(matches!(
+            coalesce.right.as_ref(),
+            Expr::LogicalExpr(LogicalExpr { op, .. }) if op.typ == Or
+        ));
+    }
+
+    #[test]
+    fn parse_nested_ternary_is_right_associative() {
+        let stmts = get_stmts("a ? b : c ? d : e;");
+
+        let Some([Stmt::ExpressionStmt(ExpressionStmt(Expr::TernaryExpr(outer)))]) =
+            stmts.as_deref()
+        else {
+            panic!("expected a ternary expression, got {:?}", stmts);
+        };
+        assert!(
+            matches!(outer.condition.as_ref(), Expr::VariableExpr(VariableExpr(name)) if name.lexeme == "a")
+        );
+        assert!(
+            matches!(outer.then_expr.as_ref(), Expr::VariableExpr(VariableExpr(name)) if name.lexeme == "b")
+        );
+        assert!(matches!(
+            outer.else_expr.as_ref(),
+            Expr::TernaryExpr(TernaryExpr { condition, .. })
+                if matches!(condition.as_ref(), Expr::VariableExpr(VariableExpr(name)) if name.lexeme == "c")
+        ));
+    }
+
+    #[test]
+    fn parse_ternary_missing_colon() {
+        assert_eq!(get_stmts("a ? b;"), None);
+    }
+
+    #[test]
+    fn parse_comma_does_not_swallow_arguments() {
+        let stmts = get_stmts("f((1, 2), 3);");
+
+        let Some([Stmt::ExpressionStmt(ExpressionStmt(Expr::CallExpr(call)))]) = stmts.as_deref()
+        else {
+            panic!("expected a call expression, got {:?}", stmts);
+        };
+        assert_eq!(call.args.len(), 2);
+        assert!(matches!(
+            &call.args[0],
+            Expr::GroupingExpr(GroupingExpr { expr: inner, .. })
+                if matches!(inner.as_ref(), Expr::BinaryExpr(BinaryExpr { op, .. }) if op.typ == Comma)
+        ));
+    }
+
+    #[test]
+    fn parse_function_declaration() {
+        let stmts = get_stmts("fun add(a, b) { return a + b; }");
+
+        let Some([Stmt::FunctionStmt(function)]) = stmts.as_deref() else {
+            panic!("expected a function declaration, got {:?}", stmts);
+        };
+        assert_eq!(function.name.lexeme, "add");
+        assert_eq!(
+            function
+                .params
+                .iter()
+                .map(|param| param.lexeme.as_str())
+                .collect::<Vec<_>>(),
+            vec!["a", "b"]
+        );
+        assert!(matches!(
+            function.body.as_slice(),
+            [Stmt::ReturnStmt(ReturnStmt { value: Some(_), .. })]
+        ));
+    }
+
+    #[test]
+    fn parse_anonymous_function() {
+        let stmts = get_stmts("var f = fun (x) { return x + 1; };");
+
+        let Some(
+            [Stmt::VarStmt(VarStmt {
+                initializer: Some(Expr::FunctionExpr(FunctionExpr(function))),
+                ..
+            })],
+        ) = stmts.as_deref()
+        else {
+            panic!("expected an anonymous function, got {:?}", stmts);
+        };
+        assert_eq!(function.name.lexeme, "");
+        assert_eq!(function.params.len(), 1);
+        assert_eq!(function.body.len(), 1);
+    }
+
+    #[test]
+    fn parse_anonymous_function_statement() {
+        let stmts = get_stmts("fun () {};");
+
+        assert!(matches!(
+            stmts.as_deref(),
+            Some([Stmt::ExpressionStmt(ExpressionStmt(Expr::FunctionExpr(_)))])
+        ));
+    }
+
+    #[test]
+    fn parse_named_function_expression() {
+        assert_eq!(get_stmts("var f = fun g() {};"), None);
+    }
+
+    #[test]
+    fn parse_too_many_arguments() {
+        let args = vec!["1"; 256].join(", ");
+        let source = format!("f({});\nprint 2;", args);
+        let mut error_reporter = ErrorReporter::default();
+        let tokens = Scanner::new(source, &mut error_reporter).scan_tokens();
+        let errors = Parser::new(&tokens).parse_program().unwrap_err();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message, "Can't have more than 255 arguments.");
+    }
+
+    #[test]
+    fn parse_too_many_parameters() {
+        let params = (0..256)
+            .map(|i| format!("p{}", i))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let source = format!("fun f({}) {{}}", params);
+        let mut error_reporter = ErrorReporter::default();
+        let tokens = Scanner::new(source, &mut error_reporter).scan_tokens();
+        let errors = Parser::new(&tokens).parse_program().unwrap_err();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].message, "Can't have more than 255 parameters.");
+    }
+
+    #[test]
+    fn parse_compound_assignment() {
+        let stmts = get_stmts("x += 2;");
+
+        let Some([Stmt::ExpressionStmt(ExpressionStmt(Expr::AssignExpr(assign)))]) =
+            stmts.as_deref()
+        else {
+            panic!("expected an assignment, got {:?}", stmts);
+        };
+        assert_eq!(assign.name.lexeme, "x");
+        let Expr::BinaryExpr(BinaryExpr { left, op, right }) = assign.value.as_ref() else {
+            panic!("expected a binary expression, got {:?}", assign.value);
+        };
+        assert_eq!(op.typ, Plus);
+        assert!(
+            matches!(left.as_ref(), Expr::VariableExpr(VariableExpr(name)) if name.lexeme == "x")
+        );
+        assert!(matches!(
+            right.as_ref(),
+            Expr::LiteralExpr(LiteralExpr(token)) if token.typ == Number(2.0)
+        ));
+    }
+
+    #[test]
+    fn parse_compound_assignment_operators() {
+        for (source, typ) in [("x -= 1;", Minus), ("x *= 1;", Star), ("x /= 1;", Slash)] {
+            let stmts = get_stmts(source);
+            assert!(
+                matches!(
+                    stmts.as_deref(),
+                    Some([Stmt::ExpressionStmt(ExpressionStmt(Expr::AssignExpr(AssignExpr {
+                        value,
+                        ..
+                    })))]) if matches!(value.as_ref(), Expr::BinaryExpr(BinaryExpr { op, .. }) if op.typ == typ)
+                ),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn parse_invalid_compound_assignment_target() {
+        assert_eq!(get_stmts("a.b += 1;"), None);
+        assert_eq!(get_stmts("1 += 1;"), None);
+    }
+
+    #[test]
+    fn parse_call() {
+        let stmts = get_stmts("f(1, 2)();");
+
+        let Some([Stmt::ExpressionStmt(ExpressionStmt(Expr::CallExpr(outer)))]) = stmts.as_deref()
+        else {
+            panic!("expected a call expression, got {:?}", stmts);
+        };
+        assert!(outer.args.is_empty());
+        assert!(matches!(
+            outer.callee.as_ref(),
+            Expr::CallExpr(CallExpr { args, .. }) if args.len() == 2
+        ));
+    }
+
+    #[test]
+    fn parse_trailing_commas() {
+        let stmts = get_stmts("f(1, 2,); fun g(a, b,) {}");
+
+        let Some(
+            [Stmt::ExpressionStmt(ExpressionStmt(Expr::CallExpr(call))), Stmt::FunctionStmt(function)],
+        ) = stmts.as_deref()
+        else {
+            panic!("expected a call and a function, got {:?}", stmts);
+        };
+        assert_eq!(call.args.len(), 2);
+        assert_eq!(function.params.len(), 2);
+    }
+
+    #[test]
+    fn parse_rejects_extra_commas() {
+        assert_eq!(get_stmts("f(1,,2);"), None);
+        assert_eq!(get_stmts("f(1,,);"), None);
+        assert_eq!(get_stmts("f(,);"), None);
+        assert_eq!(get_stmts("fun g(a,,b) {}"), None);
+    }
+
+    #[test]
+    fn parse_property_chain() {
+        let stmts = get_stmts("a.b.c;");
+
+        let Some([Stmt::ExpressionStmt(ExpressionStmt(Expr::GetExpr(outer)))]) = stmts.as_deref()
+        else {
+            panic!("expected a property access, got {:?}", stmts);
+        };
+        assert_eq!(outer.name.lexeme, "c");
+        assert!(matches!(
+            outer.object.as_ref(),
+            Expr::GetExpr(GetExpr { object, name })
+                if name.lexeme == "b" && matches!(object.as_ref(), Expr::VariableExpr(_))
+        ));
+    }
+
+    #[test]
+    fn parse_property_after_method_call() {
+        let stmts = get_stmts("a.b().c;");
+
+        let Some([Stmt::ExpressionStmt(ExpressionStmt(Expr::GetExpr(outer)))]) = stmts.as_deref()
+        else {
+            panic!("expected a property access, got {:?}", stmts);
+        };
+        assert_eq!(outer.name.lexeme, "c");
+        let Expr::CallExpr(call) = outer.object.as_ref() else {
+            panic!("expected a call, got {:?}", outer.object);
+        };
+        assert!(matches!(
+            call.callee.as_ref(),
+            Expr::GetExpr(GetExpr { name, .. }) if name.lexeme == "b"
+        ));
+    }
+
+    #[test]
+    fn parse_list_and_index() {
+        let stmts = get_stmts("[1, 2][0];");
+
+        let Some([Stmt::ExpressionStmt(ExpressionStmt(Expr::IndexExpr(index)))]) = stmts.as_deref()
+        else {
+            panic!("expected an index expression, got {:?}", stmts);
+        };
+        assert!(matches!(
+            index.object.as_ref(),
+            Expr::ListExpr(ListExpr { elements, .. }) if elements.len() == 2
+        ));
+    }
+
+    #[test]
+    fn parse_class_declaration() {
+        let stmts = get_stmts("class Foo { bar() { this.x = 1; } baz(a) {} }");
+
+        let Some([Stmt::ClassStmt(class)]) = stmts.as_deref() else {
+            panic!("expected a class declaration, got {:?}", stmts);
+        };
+        assert_eq!(class.name.lexeme, "Foo");
+        assert_eq!(
+            class
+                .methods
+                .iter()
+                .map(|method| method.name.lexeme.as_str())
+                .collect::<Vec<_>>(),
+            vec!["bar", "baz"]
+        );
+        assert!(matches!(
+            class.methods[0].body.as_slice(),
+            [Stmt::ExpressionStmt(ExpressionStmt(Expr::SetExpr(SetExpr {
+                object,
+                ..
+            })))] if matches!(object.as_ref(), Expr::ThisExpr(_))
+        ));
+    }
+
+    #[test]
+    fn parse_getter_method() {
+        let stmts = get_stmts("class Circle { area { return 1; } scale(by) {} }");
+
+        let Some([Stmt::ClassStmt(class)]) = stmts.as_deref() else {
+            panic!("expected a class declaration, got {:?}", stmts);
+        };
+        assert!(class.methods[0].getter);
+        assert!(class.methods[0].params.is_empty());
+        assert!(!class.methods[1].getter);
+        assert_eq!(get_stmts("class Circle { area; }"), None);
+    }
+
+    #[test]
+    fn parse_class_method() {
+        let stmts = get_stmts("class Math { class square(n) { return n * n; } abs(n) {} }");
+
+        let Some([Stmt::ClassStmt(class)]) = stmts.as_deref() else {
+            panic!("expected a class declaration, got {:?}", stmts);
+        };
+        assert_eq!(class.class_methods.len(), 1);
+        assert_eq!(class.class_methods[0].name.lexeme, "square");
+        assert_eq!(class.methods.len(), 1);
+        assert_eq!(class.methods[0].name.lexeme, "abs");
+    }
+
+    #[test]
+    fn parse_this_in_class_method() {
+        assert_eq!(get_stmts("class A { class f() { return this; } }"), None);
+        assert_eq!(
+            get_stmts("class A { class f() { fun g() { return this; } } }"),
+            None
+        );
+        assert_eq!(
+            get_errors("class B < A { class f() { super.f(); } }"),
+            vec![(1, "Can't use 'super' in a class method.".to_string())]
+        );
+        assert_eq!(
+            get_errors("class A { class f() { return this; } }"),
+            vec![(1, "Can't use 'this' in a class method.".to_string())]
+        );
+        assert!(get_stmts("class A { class f() { class B { g() { return this; } } } }").is_some());
+    }
+
+    #[test]
+    fn parse_subclass() {
+        let stmts = get_stmts("class B < A { greet() { return super.greet(); } }");
+
+        let Some([Stmt::ClassStmt(class)]) = stmts.as_deref() else {
+            panic!("expected a class declaration, got {:?}", stmts);
+        };
+        assert!(matches!(
+            &class.superclass,
+            Some(VariableExpr(token)) if token.lexeme == "A"
+        ));
+    }
+
+    #[test]
+    fn parse_super_without_superclass() {
+        assert_eq!(
+            get_errors("class A { f() { super.f(); } }"),
+            vec![(
+                1,
+                "Can't use 'super' in a class with no superclass.".to_string()
+            )]
+        );
+        assert_eq!(
+            get_errors("super.f();"),
+            vec![(1, "Can't use 'super' outside of a class.".to_string())]
+        );
+    }
+
+    #[test]
+    fn parse_class_inheriting_from_itself() {
+        assert_eq!(get_stmts("class A < A {}"), None);
+        assert_eq!(
+            get_errors("class A < A {\n  f() { return 1; }\n}"),
+            vec![(1, "A class can't inherit from itself.".to_string())]
+        );
+    }
+
+    #[test]
+    fn parse_prefix_and_postfix_updates() {
+        let stmts = get_stmts("++i; i--;").unwrap();
+
+        let updates = stmts
+            .iter()
+            .map(|stmt| match stmt {
+                Stmt::ExpressionStmt(ExpressionStmt(Expr::UpdateExpr(update))) => {
+                    (update.op.typ.clone(), update.prefix)
+                }
+                _ => panic!("expected an update, got {:?}", stmt),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(updates, vec![(PlusPlus, true), (MinusMinus, false)]);
+    }
+
+    #[test]
+    fn parse_update_requires_variable() {
+        assert_eq!(get_stmts("++1;"), None);
+        assert_eq!(get_stmts("a.b++;"), None);
+    }
+
+    #[test]
+    fn parse_floor_division_at_factor_precedence() {
+        let stmts = get_stmts("1 + 7 ~/ 2;");
+
+        let Some([Stmt::ExpressionStmt(ExpressionStmt(Expr::BinaryExpr(sum)))]) = stmts.as_deref()
+        else {
+            panic!("expected a binary expression, got {:?}", stmts);
+        };
+        assert_eq!(sum.op.typ, Plus);
+        assert!(matches!(
+            sum.right.as_ref(),
+            Expr::BinaryExpr(BinaryExpr { op, .. }) if op.typ == TildeSlash
+        ));
+    }
+
+    #[test]
+    fn parse_modulo_at_factor_precedence() {
+        let stmts = get_stmts("1 + 7 % 3;");
+
+        let Some([Stmt::ExpressionStmt(ExpressionStmt(Expr::BinaryExpr(sum)))]) = stmts.as_deref()
+        else {
+            panic!("expected a binary expression, got {:?}", stmts);
+        };
+        assert_eq!(sum.op.typ, Plus);
+        assert!(matches!(
+            sum.right.as_ref(),
+            Expr::BinaryExpr(BinaryExpr { op, .. }) if op.typ == Percent
+        ));
+    }
+
+    #[test]
+    fn parse_power_is_right_associative() {
+        let stmts = get_stmts("2 * 2 ** 3 ** 2;");
+
+        // Expected: 2 * (2 ** (3 ** 2))
+        let Some([Stmt::ExpressionStmt(ExpressionStmt(Expr::BinaryExpr(product)))]) =
+            stmts.as_deref()
+        else {
+            panic!("expected a binary expression, got {:?}", stmts);
+        };
+        assert_eq!(product.op.typ, Star);
+        let Expr::BinaryExpr(power) = product.right.as_ref() else {
+            panic!("expected a power expression, got {:?}", product.right);
+        };
+        assert_eq!(power.op.typ, StarStar);
+        assert!(matches!(power.left.as_ref(), Expr::LiteralExpr(_)));
+        assert!(matches!(
+            power.right.as_ref(),
+            Expr::BinaryExpr(BinaryExpr { op, .. }) if op.typ == StarStar
+        ));
+    }
+
+    #[test]
+    fn parse_lone_expression() {
+        let parse = |source: &str| {
+            let mut error_reporter = ErrorReporter::default();
+            let tokens = Scanner::new(source.to_string(), &mut error_reporter).scan_tokens();
+            Parser::new(&tokens).parse_lone_expression()
+        };
+
+        assert!(matches!(parse("1 + 2"), Some(Expr::BinaryExpr(_))));
+        assert_eq!(parse("1 + 2;"), None);
+        assert_eq!(parse("var x = 1;"), None);
+        assert_eq!(parse("1 +"), None);
+    }
+
+    #[test]
+    fn parse_missing_semicolon() {
+        let mut error_reporter = ErrorReporter::default();
+        let tokens = Scanner::new("print 1".to_string(), &mut error_reporter).scan_tokens();
+        let stmts = Parser::new(&tokens).parse_program();
+
+        assert!(stmts.is_err());
+    }
+
+    fn get_stmts(source: &str) -> Option<Vec<Stmt>> {
         let mut error_reporter = ErrorReporter::default();
-        let mut parser = Parser::new(tokens, &mut error_reporter);
-        parser.parse()
+        let tokens = Scanner::new(source.to_string(), &mut error_reporter).scan_tokens();
+        let mut parser = Parser::new(&tokens);
+        parser.parse_program().ok()
+    }
+
+    // The line and message of every parse error in `source`.
+    fn get_errors(source: &str) -> Vec<(i32, std::string::String)> {
+        let mut error_reporter = ErrorReporter::default();
+        let tokens = Scanner::new(source.to_string(), &mut error_reporter).scan_tokens();
+        let mut parser = Parser::new(&tokens);
+        parser
+            .parse_program()
+            .unwrap_err()
+            .into_iter()
+            .map(|error| (error.line, error.message))
+            .collect()
+    }
+
+    fn get_expr(tokens: Vec<Token>) -> Option<Expr> {
+        let mut parser = Parser::new(&tokens);
+        parser.parse().ok()
     }
 }
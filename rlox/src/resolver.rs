@@ -0,0 +1,450 @@
+use std::{collections::HashMap, mem, rc::Rc};
+
+use crate::{
+    error_reporter::ParseError,
+    grammar::{
+        node_id, AssignExpr, BinaryExpr, BlockStmt, BreakStmt, CallExpr, ClassStmt, ContinueStmt,
+        Expr, ExpressionStmt, FunctionExpr, FunctionStmt, GetExpr, GroupingExpr, IfStmt, IndexExpr,
+        ListExpr, LiteralExpr, LogicalExpr, PrintStmt, ReturnStmt, SetExpr, Stmt, SuperExpr,
+        SwitchStmt, TernaryExpr, ThisExpr, UnaryExpr, UpdateExpr, VarStmt, VariableExpr, WhileStmt,
+    },
+    interpreter::Interpreter,
+    token::{Token, TokenType},
+    visitor::{StmtVisitor, Visitor},
+};
+
+#[derive(Clone, Copy, PartialEq)]
+enum FunctionType {
+    None,
+    Function,
+    Initializer,
+    Method,
+}
+
+// Works out, before anything runs, which scope each variable use refers to
+// and hands that to the interpreter. Also reports the scoping mistakes that
+// can be caught without running the program.
+pub struct Resolver<'a> {
+    interpreter: &'a mut Interpreter,
+    // The enclosing local scopes, innermost last, mapping each declared name
+    // to whether its initializer has finished. Globals aren't tracked.
+    scopes: Vec<HashMap<String, bool>>,
+    current_function: FunctionType,
+    errors: Vec<ParseError>,
+}
+
+impl<'a> Resolver<'a> {
+    pub fn new(interpreter: &'a mut Interpreter) -> Self {
+        Self {
+            interpreter,
+            scopes: Vec::new(),
+            current_function: FunctionType::None,
+            errors: Vec::new(),
+        }
+    }
+
+    pub fn resolve(&mut self, statements: &[Stmt]) -> Result<(), Vec<ParseError>> {
+        self.resolve_stmts(statements);
+        self.finish()
+    }
+
+    pub fn resolve_expression(&mut self, expr: &Expr) -> Result<(), Vec<ParseError>> {
+        self.visit_expr(expr);
+        self.finish()
+    }
+
+    fn finish(&mut self) -> Result<(), Vec<ParseError>> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(mem::take(&mut self.errors))
+        }
+    }
+
+    fn resolve_stmts(&mut self, statements: &[Stmt]) {
+        for stmt in statements {
+            self.visit_stmt(stmt);
+        }
+    }
+
+    // Parameters and body share one scope, matching `LoxFunction::call`.
+    fn resolve_function(&mut self, function: &FunctionStmt, typ: FunctionType) {
+        let enclosing_function = mem::replace(&mut self.current_function, typ);
+
+        self.begin_scope();
+        for param in &function.params {
+            self.declare(param);
+            self.define(param);
+        }
+        self.resolve_stmts(&function.body);
+        self.end_scope();
+
+        self.current_function = enclosing_function;
+    }
+
+    // Tells the interpreter how many scopes out `name` lives from the node
+    // using it, or that it's a global when no local scope declares it.
+    fn resolve_local(&mut self, node: usize, name: &str) {
+        let depth = self
+            .scopes
+            .iter()
+            .rev()
+            .position(|scope| scope.contains_key(name));
+        self.interpreter.resolve(node, depth);
+    }
+
+    fn begin_scope(&mut self) {
+        self.scopes.push(HashMap::new());
+    }
+
+    fn end_scope(&mut self) {
+        self.scopes.pop();
+    }
+
+    fn declare(&mut self, name: &Token) {
+        let Some(scope) = self.scopes.last() else {
+            return;
+        };
+        if scope.contains_key(&name.lexeme) {
+            self.error(name, "Already a variable with this name in this scope.");
+        }
+
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.lexeme.clone(), false);
+        }
+    }
+
+    fn define(&mut self, name: &Token) {
+        self.define_name(&name.lexeme);
+    }
+
+    fn define_name(&mut self, name: &str) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), true);
+        }
+    }
+
+    fn error(&mut self, token: &Token, message: &str) {
+        self.errors.push(ParseError {
+            line: token.line,
+            column: token.column,
+            visual_column: token.visual_column,
+            lexeme: match token.typ {
+                TokenType::EOF => None,
+                _ => Some(token.lexeme.clone()),
+            },
+            message: message.to_string(),
+            exit_code: exitcode::DATAERR,
+        });
+    }
+}
+
+impl StmtVisitor<()> for Resolver<'_> {
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        match stmt {
+            Stmt::ExpressionStmt(stmt) => self.visit_expression_stmt(stmt),
+            Stmt::PrintStmt(stmt) => self.visit_print_stmt(stmt),
+            Stmt::VarStmt(stmt) => self.visit_var_stmt(stmt),
+            Stmt::BlockStmt(stmt) => self.visit_block_stmt(stmt),
+            Stmt::IfStmt(stmt) => self.visit_if_stmt(stmt),
+            Stmt::WhileStmt(stmt) => self.visit_while_stmt(stmt),
+            Stmt::FunctionStmt(stmt) => self.visit_function_stmt(stmt),
+            Stmt::ReturnStmt(stmt) => self.visit_return_stmt(stmt),
+            Stmt::BreakStmt(stmt) => self.visit_break_stmt(stmt),
+            Stmt::ContinueStmt(stmt) => self.visit_continue_stmt(stmt),
+            Stmt::ClassStmt(stmt) => self.visit_class_stmt(stmt),
+            Stmt::SwitchStmt(stmt) => self.visit_switch_stmt(stmt),
+        }
+    }
+
+    fn visit_expression_stmt(&mut self, stmt: &ExpressionStmt) {
+        self.visit_expr(&stmt.0);
+    }
+
+    fn visit_print_stmt(&mut self, stmt: &PrintStmt) {
+        self.visit_expr(&stmt.0);
+    }
+
+    fn visit_var_stmt(&mut self, stmt: &VarStmt) {
+        // Declared before and defined after the initializer, so the
+        // initializer can't see the variable it initializes.
+        self.declare(&stmt.name);
+        if let Some(initializer) = &stmt.initializer {
+            self.visit_expr(initializer);
+        }
+        self.define(&stmt.name);
+    }
+
+    fn visit_block_stmt(&mut self, stmt: &BlockStmt) {
+        self.begin_scope();
+        self.resolve_stmts(&stmt.0);
+        self.end_scope();
+    }
+
+    fn visit_if_stmt(&mut self, stmt: &IfStmt) {
+        self.visit_expr(&stmt.condition);
+        self.visit_stmt(&stmt.then_branch);
+        if let Some(else_branch) = &stmt.else_branch {
+            self.visit_stmt(else_branch);
+        }
+    }
+
+    fn visit_while_stmt(&mut self, stmt: &WhileStmt) {
+        self.visit_expr(&stmt.condition);
+        self.visit_stmt(&stmt.body);
+        if let Some(increment) = &stmt.increment {
+            self.visit_expr(increment);
+        }
+    }
+
+    fn visit_function_stmt(&mut self, stmt: &Rc<FunctionStmt>) {
+        // Defined up front so the function can call itself.
+        self.declare(&stmt.name);
+        self.define(&stmt.name);
+        self.resolve_function(stmt, FunctionType::Function);
+    }
+
+    fn visit_return_stmt(&mut self, stmt: &ReturnStmt) {
+        if self.current_function == FunctionType::None {
+            self.error(&stmt.keyword, "Can't return from top-level code.");
+        }
+        if let Some(value) = &stmt.value {
+            if self.current_function == FunctionType::Initializer {
+                self.error(&stmt.keyword, "Can't return a value from an initializer.");
+            }
+            self.visit_expr(value);
+        }
+    }
+
+    fn visit_break_stmt(&mut self, _: &BreakStmt) {}
+
+    fn visit_continue_stmt(&mut self, _: &ContinueStmt) {}
+
+    fn visit_class_stmt(&mut self, stmt: &ClassStmt) {
+        self.declare(&stmt.name);
+        self.define(&stmt.name);
+
+        // Mirrors the scopes the interpreter wraps around methods: one
+        // holding `super` for subclasses, then one holding `this`.
+        if let Some(superclass) = &stmt.superclass {
+            self.visit_variable_expr(superclass);
+            self.begin_scope();
+            self.define_name("super");
+        }
+        // Class methods are never bound, so they sit outside the `this` scope.
+        for method in &stmt.class_methods {
+            self.resolve_function(method, FunctionType::Method);
+        }
+        self.begin_scope();
+        self.define_name("this");
+
+        for method in &stmt.methods {
+            let typ = match method.name.lexeme.as_str() {
+                "init" => FunctionType::Initializer,
+                _ => FunctionType::Method,
+            };
+            self.resolve_function(method, typ);
+        }
+
+        self.end_scope();
+        if stmt.superclass.is_some() {
+            self.end_scope();
+        }
+    }
+
+    fn visit_switch_stmt(&mut self, stmt: &SwitchStmt) {
+        self.visit_expr(&stmt.discriminant);
+        for case in &stmt.cases {
+            self.visit_expr(&case.value);
+            self.begin_scope();
+            self.resolve_stmts(&case.body);
+            self.end_scope();
+        }
+        if let Some(default) = &stmt.default {
+            self.begin_scope();
+            self.resolve_stmts(default);
+            self.end_scope();
+        }
+    }
+}
+
+impl Visitor<()> for Resolver<'_> {
+    fn visit_expr(&mut self, expr: &Expr) {
+        use Expr::*;
+
+        match expr {
+            LiteralExpr(lexpr) => self.visit_literal_expr(lexpr),
+            UnaryExpr(uexpr) => self.visit_unary_expr(uexpr),
+            BinaryExpr(bexpr) => self.visit_binary_expr(bexpr),
+            GroupingExpr(gexpr) => self.visit_grouping_expr(gexpr),
+            VariableExpr(vexpr) => self.visit_variable_expr(vexpr),
+            AssignExpr(aexpr) => self.visit_assign_expr(aexpr),
+            LogicalExpr(lexpr) => self.visit_logical_expr(lexpr),
+            CallExpr(cexpr) => self.visit_call_expr(cexpr),
+            GetExpr(gexpr) => self.visit_get_expr(gexpr),
+            SetExpr(sexpr) => self.visit_set_expr(sexpr),
+            ThisExpr(texpr) => self.visit_this_expr(texpr),
+            SuperExpr(sexpr) => self.visit_super_expr(sexpr),
+            TernaryExpr(texpr) => self.visit_ternary_expr(texpr),
+            FunctionExpr(fexpr) => self.visit_function_expr(fexpr),
+            ListExpr(lexpr) => self.visit_list_expr(lexpr),
+            IndexExpr(iexpr) => self.visit_index_expr(iexpr),
+            UpdateExpr(uexpr) => self.visit_update_expr(uexpr),
+        }
+    }
+
+    fn visit_literal_expr(&mut self, _: &LiteralExpr) {}
+
+    fn visit_unary_expr(&mut self, expr: &UnaryExpr) {
+        self.visit_expr(&expr.expr);
+    }
+
+    fn visit_binary_expr(&mut self, expr: &BinaryExpr) {
+        self.visit_expr(&expr.left);
+        self.visit_expr(&expr.right);
+    }
+
+    fn visit_grouping_expr(&mut self, expr: &GroupingExpr) {
+        self.visit_expr(&expr.expr);
+    }
+
+    fn visit_variable_expr(&mut self, expr: &VariableExpr) {
+        let name = &expr.0;
+        let in_own_initializer = self
+            .scopes
+            .last()
+            .is_some_and(|scope| scope.get(&name.lexeme) == Some(&false));
+        if in_own_initializer {
+            self.error(name, "Can't read local variable in its own initializer.");
+        }
+
+        self.resolve_local(node_id(expr), &name.lexeme);
+    }
+
+    fn visit_assign_expr(&mut self, expr: &AssignExpr) {
+        self.visit_expr(&expr.value);
+        self.resolve_local(node_id(expr), &expr.name.lexeme);
+    }
+
+    fn visit_logical_expr(&mut self, expr: &LogicalExpr) {
+        self.visit_expr(&expr.left);
+        self.visit_expr(&expr.right);
+    }
+
+    fn visit_call_expr(&mut self, expr: &CallExpr) {
+        self.visit_expr(&expr.callee);
+        for arg in &expr.args {
+            self.visit_expr(arg);
+        }
+    }
+
+    fn visit_get_expr(&mut self, expr: &GetExpr) {
+        self.visit_expr(&expr.object);
+    }
+
+    fn visit_set_expr(&mut self, expr: &SetExpr) {
+        self.visit_expr(&expr.value);
+        self.visit_expr(&expr.object);
+    }
+
+    fn visit_this_expr(&mut self, expr: &ThisExpr) {
+        self.resolve_local(node_id(expr), "this");
+    }
+
+    fn visit_super_expr(&mut self, expr: &SuperExpr) {
+        self.resolve_local(node_id(expr), "super");
+    }
+
+    fn visit_ternary_expr(&mut self, expr: &TernaryExpr) {
+        self.visit_expr(&expr.condition);
+        self.visit_expr(&expr.then_expr);
+        self.visit_expr(&expr.else_expr);
+    }
+
+    fn visit_function_expr(&mut self, expr: &FunctionExpr) {
+        self.resolve_function(&expr.0, FunctionType::Function);
+    }
+
+    fn visit_list_expr(&mut self, expr: &ListExpr) {
+        for element in &expr.elements {
+            self.visit_expr(element);
+        }
+    }
+
+    fn visit_index_expr(&mut self, expr: &IndexExpr) {
+        self.visit_expr(&expr.object);
+        self.visit_expr(&expr.index);
+    }
+
+    fn visit_update_expr(&mut self, expr: &UpdateExpr) {
+        self.resolve_local(node_id(expr), &expr.name.lexeme);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error_reporter::ErrorReporter, parser::Parser, scanner::Scanner};
+
+    fn resolve(source: &str) -> Result<(), Vec<ParseError>> {
+        let mut error_reporter = ErrorReporter::new();
+        let tokens = Scanner::new(source.to_string(), &mut error_reporter).scan_tokens();
+        let statements = Parser::new(&tokens).parse_program().unwrap();
+        let mut interpreter = Interpreter::new();
+
+        Resolver::new(&mut interpreter).resolve(&statements)
+    }
+
+    fn messages(source: &str) -> Vec<String> {
+        match resolve(source) {
+            Ok(()) => Vec::new(),
+            Err(errors) => errors.into_iter().map(|error| error.message).collect(),
+        }
+    }
+
+    #[test]
+    fn resolve_valid_program() {
+        assert_eq!(
+            resolve("var a = 1; { var b = a; fun f(c) { return b + c; } }"),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn resolve_read_in_own_initializer() {
+        assert_eq!(
+            messages("var a = 1;\n{ var a = a; }"),
+            vec!["Can't read local variable in its own initializer."]
+        );
+    }
+
+    #[test]
+    fn resolve_global_in_own_initializer_is_allowed() {
+        assert_eq!(resolve("var a = 1; var a = a;"), Ok(()));
+    }
+
+    #[test]
+    fn resolve_top_level_return() {
+        assert_eq!(
+            messages("return 1;"),
+            vec!["Can't return from top-level code."]
+        );
+    }
+
+    #[test]
+    fn resolve_duplicate_local() {
+        assert_eq!(
+            messages("{ var a = 1; var a = 2; }"),
+            vec!["Already a variable with this name in this scope."]
+        );
+    }
+
+    #[test]
+    fn resolve_value_returned_from_initializer() {
+        assert_eq!(
+            messages("class A { init() { return 1; } }"),
+            vec!["Can't return a value from an initializer."]
+        );
+        assert_eq!(resolve("class A { init() { return; } }"), Ok(()));
+    }
+}
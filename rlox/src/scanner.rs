@@ -1,41 +1,73 @@
-use std::iter::FromIterator;
+use std::{borrow::Cow, collections::VecDeque, iter::FromIterator};
 
-use crate::error_reporter::ErrorReporter;
+use crate::error_reporter::{ErrorReporter, ScanError};
 use crate::token::{Token, TokenType, KEYWORDS};
 
 pub struct Scanner<'a> {
-    pub source: Vec<char>,
-    pub tokens: Vec<Token>,
+    // Either owned by the scanner or borrowed for `'a`, the same lifetime as
+    // the error reporter, so a caller can reuse one buffer across scans.
+    pub source: Cow<'a, [char]>,
     start: usize,
     current: usize,
     line: i32,
+    // Index of the first character of the current line.
+    line_start: usize,
+    // Column of the character at `start`.
+    start_column: usize,
+    // Where `start_column` is displayed once tabs before it are expanded.
+    start_visual_column: usize,
+    // An index on the current line and the visual column of the character
+    // there, so visual columns are worked out without rescanning the line.
+    visual_cursor: (usize, usize),
+    // Whether comments become tokens instead of being skipped, for tools
+    // such as formatters that need them.
+    keep_comments: bool,
+    // Tokens and errors scanned but not yet handed out by `next`.
+    pending: VecDeque<Result<Token, ScanError>>,
+    // Whether the EOF token has been produced.
+    finished: bool,
 
     error_reporter: &'a mut ErrorReporter,
 }
 
 impl<'a> Scanner<'a> {
-    pub fn new(source: String, error_reporter: &'a mut ErrorReporter) -> Scanner {
-        let source = source.chars().collect::<Vec<_>>();
+    pub fn new(source: String, error_reporter: &'a mut ErrorReporter) -> Scanner<'a> {
+        Self::from_chars(source.chars().collect(), error_reporter)
+    }
+
+    pub fn from_chars(source: Vec<char>, error_reporter: &'a mut ErrorReporter) -> Scanner<'a> {
+        Self::with_source(Cow::Owned(source), error_reporter)
+    }
+
+    pub fn from_slice(source: &'a [char], error_reporter: &'a mut ErrorReporter) -> Scanner<'a> {
+        Self::with_source(Cow::Borrowed(source), error_reporter)
+    }
 
+    fn with_source(source: Cow<'a, [char]>, error_reporter: &'a mut ErrorReporter) -> Scanner<'a> {
         Scanner {
             source,
-            tokens: Vec::new(),
             start: 0,
             current: 0,
             line: 1,
+            line_start: 0,
+            start_column: 1,
+            start_visual_column: 1,
+            visual_cursor: (0, 1),
+            keep_comments: false,
+            pending: VecDeque::new(),
+            finished: false,
             error_reporter,
         }
     }
 
-    pub fn scan_tokens(&mut self) -> Vec<Token> {
-        while !self.is_at_end() {
-            self.start = self.current;
-            self.scan_token();
-        }
+    pub fn set_keep_comments(&mut self, keep_comments: bool) {
+        self.keep_comments = keep_comments;
+    }
 
-        self.tokens
-            .push(Token::new(TokenType::EOF, "".into(), self.line));
-        self.tokens.to_owned()
+    // Scans the rest of the source at once. Errors go to the error reporter
+    // only, so the result holds just the tokens.
+    pub fn scan_tokens(self) -> Vec<Token> {
+        self.filter_map(Result::ok).collect()
     }
 
     fn scan_token(&mut self) {
@@ -45,70 +77,106 @@ impl<'a> Scanner<'a> {
         match *c {
             '(' => self.add_token(LeftParen),
             ')' => self.add_token(RightParen),
+            '[' => self.add_token(LeftBracket),
+            ']' => self.add_token(RightBracket),
             '{' => self.add_token(LeftBrace),
             '}' => self.add_token(RightBrace),
             ',' => self.add_token(Comma),
             '.' => self.add_token(Dot),
-            '-' => self.add_token(Minus),
-            '+' => self.add_token(Plus),
             ';' => self.add_token(SemiColon),
-            '*' => self.add_token(Star),
+            '%' => self.add_token(Percent),
+            '?' => {
+                if self.match_char('?') {
+                    self.add_token(QuestionQuestion);
+                } else {
+                    self.add_token(Question);
+                }
+            }
+            ':' => self.add_token(Colon),
             '!' => {
-                if self.is_same('=') {
-                    self.advance();
+                if self.match_char('=') {
                     self.add_token(BangEqual);
                 } else {
                     self.add_token(Bang);
                 }
             }
+            '-' => {
+                if self.match_char('-') {
+                    self.add_token(MinusMinus);
+                } else if self.match_char('=') {
+                    self.add_token(MinusEqual);
+                } else {
+                    self.add_token(Minus);
+                }
+            }
+            '+' => {
+                if self.match_char('+') {
+                    self.add_token(PlusPlus);
+                } else if self.match_char('=') {
+                    self.add_token(PlusEqual);
+                } else {
+                    self.add_token(Plus);
+                }
+            }
+            '*' => {
+                if self.match_char('*') {
+                    self.add_token(StarStar);
+                } else if self.match_char('=') {
+                    self.add_token(StarEqual);
+                } else {
+                    self.add_token(Star);
+                }
+            }
             '=' => {
-                if self.is_same('=') {
-                    self.advance();
+                if self.match_char('=') {
                     self.add_token(EqualEqual);
                 } else {
                     self.add_token(Equal);
                 }
             }
             '>' => {
-                if self.is_same('=') {
-                    self.advance();
+                if self.match_char('=') {
                     self.add_token(GreaterEqual);
                 } else {
                     self.add_token(Greater);
                 }
             }
             '<' => {
-                if self.is_same('=') {
-                    self.advance();
+                if self.match_char('=') {
                     self.add_token(LessEqual);
                 } else {
                     self.add_token(Less);
                 }
             }
             '/' => {
-                if self.is_same('/') {
+                if self.match_char('/') {
                     self.scan_inline_comment();
-                } else if self.is_same('*') {
+                } else if self.match_char('*') {
                     self.scan_block_comment();
+                } else if self.match_char('=') {
+                    self.add_token(SlashEqual);
                 } else {
                     self.add_token(Slash);
                 }
             }
+            '~' => {
+                if self.match_char('/') {
+                    self.add_token(TildeSlash);
+                } else {
+                    self.error(self.line, "Unrecognized character: ~");
+                }
+            }
             '"' => self.scan_string(),
             '_' | 'a'..='z' | 'A'..='Z' => self.scan_identifier(),
             ' ' | '\t' | '\r' => (),
-            '\n' => self.line += 1,
+            '\n' => self.new_line(),
             c => {
                 if self.is_alpha(c) {
                     self.scan_identifier();
                 } else if self.is_numeric(c) {
                     self.scan_number();
                 } else {
-                    self.error_reporter.error(
-                        self.line,
-                        format!("Unrecognized character: {}", c).as_str(),
-                        exitcode::DATAERR,
-                    );
+                    self.error(self.line, &format!("Unrecognized character: {}", c));
                 }
             }
         }
@@ -116,62 +184,163 @@ impl<'a> Scanner<'a> {
 
     fn add_token(&mut self, typ: TokenType) {
         let lexeme = self.get_lexeme();
-        let token = Token::new(typ, lexeme, self.line);
+        let token = Token::new(
+            typ,
+            lexeme,
+            self.line,
+            self.start_column,
+            self.start_visual_column,
+            self.start,
+            self.current,
+        );
 
-        self.tokens.push(token);
+        self.pending.push_back(Ok(token));
+    }
+
+    // Reports an error at the start of the current token. `line` isn't
+    // always the current one, e.g. for an unterminated block comment.
+    fn error(&mut self, line: i32, message: &str) {
+        self.error_at(line, self.start_column, self.start_visual_column, message);
+    }
+
+    // Errors are reported as they're found, and also yielded by `next` in
+    // order with the tokens around them.
+    fn error_at(&mut self, line: i32, column: usize, visual_column: usize, message: &str) {
+        let error = ScanError {
+            line,
+            column,
+            visual_column,
+            lexeme: self.get_lexeme(),
+            message: message.to_string(),
+            exit_code: exitcode::DATAERR,
+        };
+        self.error_reporter.scan_error(&error);
+        self.pending.push_back(Err(error));
     }
 
     fn scan_inline_comment(&mut self) {
         while self.peek() != '\n' && !self.is_at_end() {
             self.advance();
         }
+        self.add_comment(self.line, self.current);
     }
 
     fn scan_block_comment(&mut self) {
-        // Consume "*" in the opening "/*" of the block comment.
-        self.advance();
+        let opening_line = self.line;
+        // The opening "/*" has already been consumed.
+        let mut depth = 1;
 
-        while !(self.is_same('*') && self.peek_next() == '/' || self.is_at_end()) {
-            if self.is_same('\n') {
-                self.line += 1;
+        while depth > 0 && !self.is_at_end() {
+            if self.is_same('/') && self.peek_next() == '*' {
+                self.advance();
+                self.advance();
+                depth += 1;
+            } else if self.is_same('*') && self.peek_next() == '/' {
+                self.advance();
+                self.advance();
+                depth -= 1;
+            } else if *self.advance() == '\n' {
+                self.new_line();
             }
-            self.advance();
         }
 
-        if self.is_at_end() {
-            self.error_reporter
-                .error(self.line, "Unterminated block comment.", exitcode::DATAERR);
+        if depth > 0 {
+            self.error(opening_line, "Unterminated block comment.");
+        } else {
+            self.add_comment(opening_line, self.current - 2);
+        }
+    }
+
+    // Adds the comment that opened at `start` if comments are being kept.
+    // Its text runs from after the opening delimiter up to `text_end`, and it
+    // belongs to the line it starts on.
+    fn add_comment(&mut self, line: i32, text_end: usize) {
+        if !self.keep_comments {
             return;
         }
 
-        // Consume the closing "*/" of the block comment.
-        self.advance();
-        self.advance();
+        let text = String::from_iter(&self.source[self.start + 2..text_end]);
+        let token = Token::new(
+            TokenType::Comment(text),
+            self.get_lexeme(),
+            line,
+            self.start_column,
+            self.start_visual_column,
+            self.start,
+            self.current,
+        );
+        self.pending.push_back(Ok(token));
     }
 
     fn scan_string(&mut self) {
+        let mut value = String::new();
+
         while self.peek() != '"' && !self.is_at_end() {
-            if self.peek() == '\n' {
-                self.line += 1;
+            let c = *self.advance();
+            match c {
+                '\\' => {
+                    if let Some(escaped) = self.scan_escape() {
+                        value.push(escaped);
+                    }
+                }
+                // Normalize CRLF line endings so strings read the same on
+                // every platform.
+                '\r' if self.is_same('\n') => (),
+                '\n' => {
+                    self.new_line();
+                    value.push(c);
+                }
+                c => value.push(c),
             }
-            self.advance();
         }
 
         if self.is_at_end() {
-            self.error_reporter
-                .error(self.line, "Unterminated string.", exitcode::DATAERR);
+            let visual_column = self.visual_column_at(self.current);
+            self.error_at(
+                self.line,
+                self.current_column(),
+                visual_column,
+                "Unterminated string.",
+            );
             return;
         }
 
         // The closing ".
         self.advance();
 
-        let value = self.source[self.start + 1..self.current - 1]
-            .iter()
-            .collect::<String>();
         self.add_token(TokenType::String(value));
     }
 
+    // Translates the character following a '\' inside a string literal.
+    fn scan_escape(&mut self) -> Option<char> {
+        if self.is_at_end() {
+            // Let scan_string report the unterminated string.
+            return None;
+        }
+
+        let escaped = match *self.advance() {
+            'n' => '\n',
+            't' => '\t',
+            'r' => '\r',
+            '"' => '"',
+            '\\' => '\\',
+            '0' => '\0',
+            c => {
+                // Point at the '\' rather than the character after it.
+                let visual_column = self.visual_column_at(self.current - 2);
+                self.error_at(
+                    self.line,
+                    self.current_column() - 2,
+                    visual_column,
+                    &format!("Invalid escape sequence: \\{}", c),
+                );
+                return None;
+            }
+        };
+
+        Some(escaped)
+    }
+
     fn scan_identifier(&mut self) {
         while self.is_alphanumeric(self.peek()) {
             self.advance();
@@ -189,25 +358,130 @@ impl<'a> Scanner<'a> {
     }
 
     fn scan_number(&mut self) {
-        while self.is_numeric(self.peek()) {
+        if self.source[self.start] == '0' && (self.is_same('x') || self.is_same('X')) {
             self.advance();
+            self.scan_hex_number();
+            return;
         }
 
+        let mut separators_valid = self.scan_digits();
+
         // Consume "." and proceed consuming the real part of the number.
         if self.is_same('.') && self.is_numeric(self.peek_next()) {
             self.advance();
+            separators_valid &= self.scan_digits();
+        }
+
+        // Consume an exponent such as "e10", "E+3" or "e-9".
+        if self.is_same('e') || self.is_same('E') {
+            self.advance();
 
-            while self.is_numeric(self.peek()) {
+            if self.is_same('+') || self.is_same('-') {
                 self.advance();
             }
+
+            if !self.is_numeric(self.peek()) {
+                self.error(self.line, "Expected digits in the exponent of a number.");
+                return;
+            }
+
+            separators_valid &= self.scan_digits();
+        }
+
+        if !separators_valid {
+            self.error(self.line, "A '_' in a number must sit between two digits.");
+            return;
         }
 
         let value = self.source[self.start..self.current]
             .iter()
+            .filter(|&&c| c != '_')
             .collect::<String>()
-            .parse::<f32>()
-            .unwrap();
-        self.add_token(TokenType::Number(value));
+            .parse::<f64>();
+        match value {
+            Ok(value) => self.add_number(value),
+            Err(_) => self.number_out_of_range(),
+        }
+    }
+
+    // Literals too large for an f64 parse to infinity rather than failing,
+    // so they are caught here instead of silently becoming `inf`.
+    fn add_number(&mut self, value: f64) {
+        if value.is_finite() {
+            self.add_token(TokenType::Number(value));
+        } else {
+            self.number_out_of_range();
+        }
+    }
+
+    fn number_out_of_range(&mut self) {
+        self.error(self.line, "Number literal out of range.");
+    }
+
+    // Consumes a run of digits that may contain '_' separators, returning
+    // false if a separator isn't directly followed by a digit. Callers only
+    // start a run right after a digit, so leading separators can't occur.
+    fn scan_digits(&mut self) -> bool {
+        let mut valid = true;
+
+        while self.is_numeric(self.peek()) || self.is_same('_') {
+            if self.is_same('_') && !self.is_numeric(self.peek_next()) {
+                valid = false;
+            }
+            self.advance();
+        }
+
+        valid
+    }
+
+    // Records that the '\n' just consumed starts a new line.
+    fn new_line(&mut self) {
+        self.line += 1;
+        self.line_start = self.current;
+        self.visual_cursor = (self.current, 1);
+    }
+
+    // Column of the next character to be consumed.
+    fn current_column(&self) -> usize {
+        self.current - self.line_start + 1
+    }
+
+    // Where the character at `index` on the current line is displayed, with
+    // each tab advancing to the next tab stop.
+    fn visual_column_at(&mut self, index: usize) -> usize {
+        if index < self.visual_cursor.0 {
+            self.visual_cursor = (self.line_start, 1);
+        }
+        let tab_width = self.error_reporter.tab_width();
+        let (mut current, mut column) = self.visual_cursor;
+        for &c in &self.source[current..index] {
+            column += match c {
+                '\t' => tab_width - (column - 1) % tab_width,
+                _ => 1,
+            };
+            current += 1;
+        }
+        self.visual_cursor = (current, column);
+
+        column
+    }
+
+    // Scans the digits of a hexadecimal literal after its "0x" prefix. Lox has
+    // a single f64 number type, so values above 2^53 lose precision just like
+    // their decimal spellings would.
+    fn scan_hex_number(&mut self) {
+        let mut value = 0.0;
+        while let Some(digit) = self.peek().to_digit(16) {
+            value = value * 16.0 + f64::from(digit);
+            self.advance();
+        }
+
+        if self.current - self.start == 2 {
+            self.error(self.line, "Expected hex digits after '0x'.");
+            return;
+        }
+
+        self.add_number(value);
     }
 
     fn is_at_end(&self) -> bool {
@@ -241,12 +515,24 @@ impl<'a> Scanner<'a> {
         self.peek() == c
     }
 
+    // Consumes the current character only if it is the expected one.
+    fn match_char(&mut self, expected: char) -> bool {
+        if !self.is_same(expected) {
+            return false;
+        }
+
+        self.current += 1;
+        true
+    }
+
     fn get_lexeme(&self) -> String {
         String::from_iter(&self.source[self.start..self.current])
     }
 
+    // Identifiers may use any Unicode letter; keywords stay ASCII-only since
+    // they're looked up verbatim in KEYWORDS.
     fn is_alpha(&self, c: char) -> bool {
-        c.is_ascii_alphabetic() || c == '_'
+        c.is_alphabetic() || c == '_'
     }
 
     fn is_numeric(&self, c: char) -> bool {
@@ -254,48 +540,414 @@ impl<'a> Scanner<'a> {
     }
 
     fn is_alphanumeric(&self, c: char) -> bool {
-        self.is_alpha(c) || self.is_numeric(c)
+        c.is_alphanumeric() || c == '_'
+    }
+}
+
+// Scans lazily: each call runs as many `scan_token` cycles as it takes to
+// produce a token or an error, and the EOF token is the last item.
+impl Iterator for Scanner<'_> {
+    type Item = Result<Token, ScanError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(result) = self.pending.pop_front() {
+                return Some(result);
+            }
+            if self.finished {
+                return None;
+            }
+
+            if self.is_at_end() {
+                self.finished = true;
+                let visual_column = self.visual_column_at(self.current);
+                return Some(Ok(Token::new(
+                    TokenType::EOF,
+                    "".into(),
+                    self.line,
+                    self.current_column(),
+                    visual_column,
+                    self.current,
+                    self.current,
+                )));
+            }
+
+            self.start = self.current;
+            self.start_column = self.current_column();
+            self.start_visual_column = self.visual_column_at(self.current);
+            self.scan_token();
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::{TokenType::*, *};
+    use std::num::NonZeroUsize;
 
     #[test]
     fn scan_hello() {
         let source = "print \"Hello, world!\";".to_string();
         let mut error_reporter = ErrorReporter::new();
 
-        let mut scanner = Scanner::new(source, &mut error_reporter);
+        let scanner = Scanner::new(source, &mut error_reporter);
         let tokens = scanner.scan_tokens();
         let correct = vec![
             Token {
                 typ: Print,
                 lexeme: "print".to_string(),
                 line: 1,
+                column: 1,
+                visual_column: 1,
+                start: 0,
+                end: 5,
             },
             Token {
                 typ: String("Hello, world!".to_string()),
                 lexeme: "\"Hello, world!\"".to_string(),
                 line: 1,
+                column: 7,
+                visual_column: 7,
+                start: 6,
+                end: 21,
             },
             Token {
                 typ: SemiColon,
                 lexeme: ";".to_string(),
                 line: 1,
+                column: 22,
+                visual_column: 22,
+                start: 21,
+                end: 22,
+            },
+            Token {
+                typ: EOF,
+                lexeme: "".to_string(),
+                line: 1,
+                column: 23,
+                visual_column: 23,
+                start: 22,
+                end: 22,
+            },
+        ];
+
+        assert_eq!(tokens, correct);
+    }
+
+    #[test]
+    fn scan_token_spans() {
+        let mut error_reporter = ErrorReporter::new();
+        let tokens = Scanner::new("print \"hi\";".to_string(), &mut error_reporter).scan_tokens();
+
+        let spans = tokens
+            .iter()
+            .map(|token| (token.start, token.end))
+            .collect::<Vec<_>>();
+        assert_eq!(spans, vec![(0, 5), (6, 10), (10, 11), (11, 11)]);
+    }
+
+    #[test]
+    fn scan_string_escapes() {
+        let source = r#""line\nbreak \"quoted\" \\""#.to_string();
+        let mut error_reporter = ErrorReporter::new();
+
+        let scanner = Scanner::new(source.clone(), &mut error_reporter);
+        let tokens = scanner.scan_tokens();
+        let correct = vec![
+            Token {
+                typ: String("line\nbreak \"quoted\" \\".to_string()),
+                lexeme: source,
+                line: 1,
+                column: 1,
+                visual_column: 1,
+                start: 0,
+                end: 27,
             },
             Token {
                 typ: EOF,
                 lexeme: "".to_string(),
                 line: 1,
+                column: 28,
+                visual_column: 28,
+                start: 27,
+                end: 27,
             },
         ];
 
-        tokens
+        assert_eq!(tokens, correct);
+        assert!(!error_reporter.had_error);
+    }
+
+    #[test]
+    fn scan_invalid_string_escape() {
+        let source = r#""bad \q escape""#.to_string();
+        let mut error_reporter = ErrorReporter::new();
+
+        let scanner = Scanner::new(source, &mut error_reporter);
+        scanner.scan_tokens();
+
+        assert!(error_reporter.had_error);
+    }
+
+    #[test]
+    fn scan_same_tokens_from_every_constructor() {
+        let source = "var greeting = \"hi\";\nprint greeting;";
+        let chars = source.chars().collect::<Vec<_>>();
+        let mut error_reporter = ErrorReporter::new();
+
+        let from_string = Scanner::new(source.to_string(), &mut error_reporter).scan_tokens();
+        let from_chars = Scanner::from_chars(chars.clone(), &mut error_reporter).scan_tokens();
+        let from_slice = Scanner::from_slice(&chars, &mut error_reporter).scan_tokens();
+
+        assert_eq!(from_string, from_chars);
+        assert_eq!(from_string, from_slice);
+    }
+
+    #[test]
+    fn iterator_matches_scan_tokens() {
+        let source = "var x = 1;\nprint x + \"a\"; // done";
+        let mut error_reporter = ErrorReporter::new();
+
+        let scanned = Scanner::new(source.to_string(), &mut error_reporter).scan_tokens();
+        let iterated = Scanner::new(source.to_string(), &mut error_reporter)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(iterated, scanned);
+    }
+
+    #[test]
+    fn iterator_yields_errors_in_order() {
+        let mut error_reporter = ErrorReporter::with_output(Box::new(std::io::sink()));
+        let mut scanner = Scanner::new("1 @ 2".to_string(), &mut error_reporter);
+
+        assert!(matches!(
+            scanner.next(),
+            Some(Ok(Token { typ: Number(_), .. }))
+        ));
+        let error = scanner.next().unwrap().unwrap_err();
+        assert_eq!(error.message, "Unrecognized character: @");
+        assert_eq!(error.lexeme, "@");
+        assert_eq!((error.line, error.column), (1, 3));
+        assert!(matches!(
+            scanner.next(),
+            Some(Ok(Token { typ: Number(_), .. }))
+        ));
+        assert!(matches!(scanner.next(), Some(Ok(Token { typ: EOF, .. }))));
+        assert!(scanner.next().is_none());
+        assert!(error_reporter.had_error);
+    }
+
+    #[test]
+    fn scan_keeps_comments_when_asked() {
+        let source = "// first\nvar x; /* spans\ntwo lines */ print x; // last";
+        let mut error_reporter = ErrorReporter::new();
+        let mut scanner = Scanner::new(source.to_string(), &mut error_reporter);
+        scanner.set_keep_comments(true);
+
+        let comments = scanner
+            .scan_tokens()
             .into_iter()
-            .zip(correct)
-            .for_each(|(a, b)| assert_eq!(a, b));
+            .filter_map(|token| match token.typ {
+                Comment(text) => Some((text, token.line, token.lexeme)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            comments,
+            vec![
+                (" first".to_string(), 1, "// first".to_string()),
+                (
+                    " spans\ntwo lines ".to_string(),
+                    2,
+                    "/* spans\ntwo lines */".to_string()
+                ),
+                (" last".to_string(), 3, "// last".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn scan_skips_comments_by_default() {
+        let mut error_reporter = ErrorReporter::new();
+        let tokens =
+            Scanner::new("// note\nnil; /* more */".to_string(), &mut error_reporter).scan_tokens();
+
+        let types = tokens.iter().map(|t| t.typ.clone()).collect::<Vec<_>>();
+        assert_eq!(types, vec![Nil, SemiColon, EOF]);
+    }
+
+    #[test]
+    fn scan_tokens_leaves_out_errors() {
+        let source = "nil @;".to_string();
+        let mut error_reporter = ErrorReporter::with_output(Box::new(std::io::sink()));
+
+        let tokens = Scanner::new(source, &mut error_reporter).scan_tokens();
+
+        let types = tokens.iter().map(|t| t.typ.clone()).collect::<Vec<_>>();
+        assert_eq!(types, vec![Nil, SemiColon, EOF]);
+        assert!(error_reporter.had_error);
+    }
+
+    #[test]
+    fn scan_visual_columns_expand_tabs() {
+        let mut error_reporter = ErrorReporter::with_output(Box::new(std::io::sink()));
+        let columns = Scanner::new("\tvar\tx\n  y".to_string(), &mut error_reporter)
+            .filter_map(Result::ok)
+            .map(|token| (token.column, token.visual_column))
+            .collect::<Vec<_>>();
+        assert_eq!(columns, vec![(2, 9), (6, 17), (3, 3), (4, 4)]);
+
+        let errors = Scanner::new("\t/* open\n\tx\t\"a\\q\"".to_string(), &mut error_reporter)
+            .filter_map(Result::err)
+            .map(|error| (error.line, error.column, error.visual_column))
+            .collect::<Vec<_>>();
+        assert_eq!(errors, vec![(1, 2, 9)]);
+
+        // The width comes from the error reporter, which draws the carets.
+        error_reporter.set_tab_width(NonZeroUsize::new(4).unwrap());
+        let errors = Scanner::new("x\t @\n\t\"a\\q\"".to_string(), &mut error_reporter)
+            .filter_map(Result::err)
+            .map(|error| (error.line, error.column, error.visual_column))
+            .collect::<Vec<_>>();
+        assert_eq!(errors, vec![(1, 4, 6), (2, 4, 7)]);
+    }
+
+    #[test]
+    fn scan_records_every_unrecognized_character() {
+        let source = "@ nil\n  # $".to_string();
+        let mut error_reporter = ErrorReporter::with_output(Box::new(std::io::sink()));
+
+        let scanner = Scanner::new(source, &mut error_reporter);
+        scanner.scan_tokens();
+
+        let positions = error_reporter
+            .diagnostics()
+            .iter()
+            .map(|d| (d.line, d.column, d.message.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            positions,
+            vec![
+                (1, Some(1), "Unrecognized character: @"),
+                (2, Some(3), "Unrecognized character: #"),
+                (2, Some(5), "Unrecognized character: $"),
+            ]
+        );
+    }
+
+    #[test]
+    fn scan_number_out_of_range() {
+        let source = format!("{} nil\n0x{}", "9".repeat(400), "f".repeat(300));
+        let mut error_reporter = ErrorReporter::with_output(Box::new(std::io::sink()));
+
+        let scanner = Scanner::new(source, &mut error_reporter);
+        let tokens = scanner.scan_tokens();
+
+        let positions = error_reporter
+            .diagnostics()
+            .iter()
+            .map(|d| (d.line, d.column, d.message.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            positions,
+            vec![
+                (1, Some(1), "Number literal out of range."),
+                (2, Some(1), "Number literal out of range."),
+            ]
+        );
+        // Scanning carries on past the bad literal.
+        assert_eq!(
+            tokens.iter().map(|t| &t.typ).collect::<Vec<_>>(),
+            vec![&Nil, &EOF]
+        );
+    }
+
+    #[test]
+    fn scan_nested_block_comment() {
+        let source = "/* outer /* inner */ still outer */ nil".to_string();
+        let mut error_reporter = ErrorReporter::new();
+
+        let scanner = Scanner::new(source, &mut error_reporter);
+        let tokens = scanner.scan_tokens();
+        let correct = vec![
+            Token {
+                typ: Nil,
+                lexeme: "nil".to_string(),
+                line: 1,
+                column: 37,
+                visual_column: 37,
+                start: 36,
+                end: 39,
+            },
+            Token {
+                typ: EOF,
+                lexeme: "".to_string(),
+                line: 1,
+                column: 40,
+                visual_column: 40,
+                start: 39,
+                end: 39,
+            },
+        ];
+
+        assert_eq!(tokens, correct);
+        assert!(!error_reporter.had_error);
+    }
+
+    #[test]
+    fn scan_unterminated_nested_block_comment() {
+        let source = "/* outer\n/* inner */\nnever closed".to_string();
+        let mut error_reporter = ErrorReporter::with_output(Box::new(std::io::sink()));
+
+        let scanner = Scanner::new(source, &mut error_reporter);
+        scanner.scan_tokens();
+
+        // Reported at the outermost opener, not where the input ran out.
+        let positions = error_reporter
+            .diagnostics()
+            .iter()
+            .map(|d| (d.line, d.message.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(positions, vec![(1, "Unterminated block comment.")]);
+    }
+
+    #[test]
+    fn scan_multiline_block_comment_lines() {
+        let source = "/* first\nsecond\nthird */\n@ nil".to_string();
+        let mut error_reporter = ErrorReporter::with_output(Box::new(std::io::sink()));
+
+        let scanner = Scanner::new(source, &mut error_reporter);
+        let tokens = scanner.scan_tokens();
+        let correct = vec![
+            Token {
+                typ: Nil,
+                lexeme: "nil".to_string(),
+                line: 4,
+                column: 3,
+                visual_column: 3,
+                start: 27,
+                end: 30,
+            },
+            Token {
+                typ: EOF,
+                lexeme: "".to_string(),
+                line: 4,
+                column: 6,
+                visual_column: 6,
+                start: 30,
+                end: 30,
+            },
+        ];
+
+        assert_eq!(tokens, correct);
+        // The stray '@' after the comment is reported on line 4.
+        let positions = error_reporter
+            .diagnostics()
+            .iter()
+            .map(|d| (d.line, d.column, d.message.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(positions, vec![(4, Some(1), "Unrecognized character: @")]);
     }
 
     #[test]
@@ -311,13 +963,17 @@ mod tests {
         .to_string();
         let mut error_reporter = ErrorReporter::new();
 
-        let mut scanner = Scanner::new(source, &mut error_reporter);
+        let scanner = Scanner::new(source, &mut error_reporter);
         let tokens = scanner.scan_tokens();
         let correct = vec![
             Token {
                 typ: Fun,
                 lexeme: "fun".to_string(),
                 line: 2,
+                column: 9,
+                visual_column: 9,
+                start: 51,
+                end: 54,
             },
             Token {
                 typ: Identifier(
@@ -325,11 +981,19 @@ mod tests {
                 ),
                 lexeme: "fib".to_string(),
                 line: 2,
+                column: 13,
+                visual_column: 13,
+                start: 55,
+                end: 58,
             },
             Token {
                 typ: LeftParen,
                 lexeme: "(".to_string(),
                 line: 2,
+                column: 16,
+                visual_column: 16,
+                start: 58,
+                end: 59,
             },
             Token {
                 typ: Identifier(
@@ -337,26 +1001,46 @@ mod tests {
                 ),
                 lexeme: "n".to_string(),
                 line: 2,
+                column: 17,
+                visual_column: 17,
+                start: 59,
+                end: 60,
             },
             Token {
                 typ: RightParen,
                 lexeme: ")".to_string(),
                 line: 2,
+                column: 18,
+                visual_column: 18,
+                start: 60,
+                end: 61,
             },
             Token {
                 typ: LeftBrace,
                 lexeme: "{".to_string(),
                 line: 2,
+                column: 20,
+                visual_column: 20,
+                start: 62,
+                end: 63,
             },
             Token {
                 typ: If,
                 lexeme: "if".to_string(),
                 line: 3,
+                column: 13,
+                visual_column: 13,
+                start: 76,
+                end: 78,
             },
             Token {
                 typ: LeftParen,
                 lexeme: "(".to_string(),
                 line: 3,
+                column: 16,
+                visual_column: 16,
+                start: 79,
+                end: 80,
             },
             Token {
                 typ: Identifier(
@@ -364,11 +1048,19 @@ mod tests {
                 ),
                 lexeme: "n".to_string(),
                 line: 3,
+                column: 17,
+                visual_column: 17,
+                start: 80,
+                end: 81,
             },
             Token {
                 typ: EqualEqual,
                 lexeme: "==".to_string(),
                 line: 3,
+                column: 19,
+                visual_column: 19,
+                start: 82,
+                end: 84,
             },
             Token {
                 typ: Number(
@@ -376,11 +1068,19 @@ mod tests {
                 ),
                 lexeme: "0".to_string(),
                 line: 3,
+                column: 22,
+                visual_column: 22,
+                start: 85,
+                end: 86,
             },
             Token {
                 typ: Or,
                 lexeme: "or".to_string(),
                 line: 3,
+                column: 24,
+                visual_column: 24,
+                start: 87,
+                end: 89,
             },
             Token {
                 typ: Identifier(
@@ -388,11 +1088,19 @@ mod tests {
                 ),
                 lexeme: "n".to_string(),
                 line: 3,
+                column: 27,
+                visual_column: 27,
+                start: 90,
+                end: 91,
             },
             Token {
                 typ: EqualEqual,
                 lexeme: "==".to_string(),
                 line: 3,
+                column: 29,
+                visual_column: 29,
+                start: 92,
+                end: 94,
             },
             Token {
                 typ: Number(
@@ -400,21 +1108,37 @@ mod tests {
                 ),
                 lexeme: "1".to_string(),
                 line: 3,
+                column: 32,
+                visual_column: 32,
+                start: 95,
+                end: 96,
             },
             Token {
                 typ: RightParen,
                 lexeme: ")".to_string(),
                 line: 3,
+                column: 33,
+                visual_column: 33,
+                start: 96,
+                end: 97,
             },
             Token {
                 typ: LeftBrace,
                 lexeme: "{".to_string(),
                 line: 3,
+                column: 35,
+                visual_column: 35,
+                start: 98,
+                end: 99,
             },
             Token {
                 typ: Return,
                 lexeme: "return".to_string(),
                 line: 4,
+                column: 17,
+                visual_column: 17,
+                start: 116,
+                end: 122,
             },
             Token {
                 typ: Identifier(
@@ -422,21 +1146,37 @@ mod tests {
                 ),
                 lexeme: "n".to_string(),
                 line: 4,
+                column: 24,
+                visual_column: 24,
+                start: 123,
+                end: 124,
             },
             Token {
                 typ: SemiColon,
                 lexeme: ";".to_string(),
                 line: 4,
+                column: 25,
+                visual_column: 25,
+                start: 124,
+                end: 125,
             },
             Token {
                 typ: RightBrace,
                 lexeme: "}".to_string(),
                 line: 5,
+                column: 13,
+                visual_column: 13,
+                start: 138,
+                end: 139,
             },
             Token {
                 typ: Return,
                 lexeme: "return".to_string(),
                 line: 7,
+                column: 13,
+                visual_column: 13,
+                start: 161,
+                end: 167,
             },
             Token {
                 typ: Identifier(
@@ -444,11 +1184,19 @@ mod tests {
                 ),
                 lexeme: "fib".to_string(),
                 line: 7,
+                column: 20,
+                visual_column: 20,
+                start: 168,
+                end: 171,
             },
             Token {
                 typ: LeftParen,
                 lexeme: "(".to_string(),
                 line: 7,
+                column: 23,
+                visual_column: 23,
+                start: 171,
+                end: 172,
             },
             Token {
                 typ: Identifier(
@@ -456,11 +1204,19 @@ mod tests {
                 ),
                 lexeme: "n".to_string(),
                 line: 7,
+                column: 24,
+                visual_column: 24,
+                start: 172,
+                end: 173,
             },
             Token {
                 typ: Minus,
                 lexeme: "-".to_string(),
                 line: 7,
+                column: 25,
+                visual_column: 25,
+                start: 173,
+                end: 174,
             },
             Token {
                 typ: Number(
@@ -468,16 +1224,28 @@ mod tests {
                 ),
                 lexeme: "1".to_string(),
                 line: 7,
+                column: 26,
+                visual_column: 26,
+                start: 174,
+                end: 175,
             },
             Token {
                 typ: RightParen,
                 lexeme: ")".to_string(),
                 line: 7,
+                column: 27,
+                visual_column: 27,
+                start: 175,
+                end: 176,
             },
             Token {
                 typ: Plus,
                 lexeme: "+".to_string(),
                 line: 7,
+                column: 29,
+                visual_column: 29,
+                start: 177,
+                end: 178,
             },
             Token {
                 typ: Identifier(
@@ -485,11 +1253,19 @@ mod tests {
                 ),
                 lexeme: "fib".to_string(),
                 line: 7,
+                column: 31,
+                visual_column: 31,
+                start: 179,
+                end: 182,
             },
             Token {
                 typ: LeftParen,
                 lexeme: "(".to_string(),
                 line: 7,
+                column: 34,
+                visual_column: 34,
+                start: 182,
+                end: 183,
             },
             Token {
                 typ: Identifier(
@@ -497,11 +1273,19 @@ mod tests {
                 ),
                 lexeme: "n".to_string(),
                 line: 7,
+                column: 35,
+                visual_column: 35,
+                start: 183,
+                end: 184,
             },
             Token {
                 typ: Minus,
                 lexeme: "-".to_string(),
                 line: 7,
+                column: 36,
+                visual_column: 36,
+                start: 184,
+                end: 185,
             },
             Token {
                 typ: Number(
@@ -509,174 +1293,887 @@ mod tests {
                 ),
                 lexeme: "2".to_string(),
                 line: 7,
+                column: 37,
+                visual_column: 37,
+                start: 185,
+                end: 186,
             },
             Token {
                 typ: RightParen,
                 lexeme: ")".to_string(),
                 line: 7,
+                column: 38,
+                visual_column: 38,
+                start: 186,
+                end: 187,
             },
             Token {
                 typ: SemiColon,
                 lexeme: ";".to_string(),
                 line: 7,
+                column: 39,
+                visual_column: 39,
+                start: 187,
+                end: 188,
             },
             Token {
                 typ: RightBrace,
                 lexeme: "}".to_string(),
                 line: 8,
+                column: 9,
+                visual_column: 9,
+                start: 197,
+                end: 198,
             },
             Token {
                 typ: EOF,
                 lexeme: "".to_string(),
                 line: 8,
+                column: 10,
+                visual_column: 10,
+                start: 198,
+                end: 198,
             },
         ];
 
-        tokens
+        assert_eq!(tokens, correct);
+    }
+
+    #[test]
+    fn scan_increment_and_decrement() {
+        let mut error_reporter = ErrorReporter::new();
+        let tokens = Scanner::new("++i - -j--".to_string(), &mut error_reporter).scan_tokens();
+
+        let types = tokens
             .into_iter()
-            .zip(correct)
-            .for_each(|(a, b)| assert_eq!(a, b));
+            .map(|token| token.typ)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            vec![
+                PlusPlus,
+                Identifier("i".to_string()),
+                Minus,
+                Minus,
+                Identifier("j".to_string()),
+                MinusMinus,
+                EOF
+            ]
+        );
     }
 
     #[test]
-    fn scan_comparators() {
-        let source = "1 == 2; 1 != 2; 1 < 2; 1 <= 2; 1 > 2; 1 >= 2;".to_string();
+    fn scan_floor_division() {
+        let mut error_reporter = ErrorReporter::new();
+        let source = "7 ~/ 2 // 3".to_string();
+        let tokens = Scanner::new(source, &mut error_reporter).scan_tokens();
+
+        let types = tokens
+            .into_iter()
+            .map(|token| token.typ)
+            .collect::<Vec<_>>();
+        assert_eq!(types, vec![Number(7.0), TildeSlash, Number(2.0), EOF]);
+    }
+
+    #[test]
+    fn scan_lone_tilde() {
         let mut error_reporter = ErrorReporter::new();
+        Scanner::new("7 ~ 2".to_string(), &mut error_reporter).scan_tokens();
 
-        let mut scanner = Scanner::new(source, &mut error_reporter);
+        assert!(error_reporter.had_error);
+    }
+
+    #[test]
+    fn scan_modulo() {
+        let source = "7 % 3".to_string();
+        let mut error_reporter = ErrorReporter::new();
+
+        let scanner = Scanner::new(source, &mut error_reporter);
         let tokens = scanner.scan_tokens();
         let correct = vec![
-            // 1 == 2;
             Token {
-                typ: Number(1.0),
-                lexeme: "1".to_string(),
+                typ: Number(7.0),
+                lexeme: "7".to_string(),
                 line: 1,
+                column: 1,
+                visual_column: 1,
+                start: 0,
+                end: 1,
+            },
+            Token {
+                typ: Percent,
+                lexeme: "%".to_string(),
+                line: 1,
+                column: 3,
+                visual_column: 3,
+                start: 2,
+                end: 3,
+            },
+            Token {
+                typ: Number(3.0),
+                lexeme: "3".to_string(),
+                line: 1,
+                column: 5,
+                visual_column: 5,
+                start: 4,
+                end: 5,
+            },
+            Token {
+                typ: EOF,
+                lexeme: "".to_string(),
+                line: 1,
+                column: 6,
+                visual_column: 6,
+                start: 5,
+                end: 5,
+            },
+        ];
+
+        assert_eq!(tokens, correct);
+    }
+
+    #[test]
+    fn scan_power() {
+        let source = "2 * 3 ** 2".to_string();
+        let mut error_reporter = ErrorReporter::new();
+
+        let scanner = Scanner::new(source, &mut error_reporter);
+        let tokens = scanner.scan_tokens();
+        let correct = vec![
+            Token {
+                typ: Number(2.0),
+                lexeme: "2".to_string(),
+                line: 1,
+                column: 1,
+                visual_column: 1,
+                start: 0,
+                end: 1,
+            },
+            Token {
+                typ: Star,
+                lexeme: "*".to_string(),
+                line: 1,
+                column: 3,
+                visual_column: 3,
+                start: 2,
+                end: 3,
+            },
+            Token {
+                typ: Number(3.0),
+                lexeme: "3".to_string(),
+                line: 1,
+                column: 5,
+                visual_column: 5,
+                start: 4,
+                end: 5,
+            },
+            Token {
+                typ: StarStar,
+                lexeme: "**".to_string(),
+                line: 1,
+                column: 7,
+                visual_column: 7,
+                start: 6,
+                end: 8,
+            },
+            Token {
+                typ: Number(2.0),
+                lexeme: "2".to_string(),
+                line: 1,
+                column: 10,
+                visual_column: 10,
+                start: 9,
+                end: 10,
+            },
+            Token {
+                typ: EOF,
+                lexeme: "".to_string(),
+                line: 1,
+                column: 11,
+                visual_column: 11,
+                start: 10,
+                end: 10,
+            },
+        ];
+
+        assert_eq!(tokens, correct);
+    }
+
+    #[test]
+    fn scan_compound_assignment_operators() {
+        let source = "+= -= *= /= + - * /".to_string();
+        let mut error_reporter = ErrorReporter::new();
+
+        let scanner = Scanner::new(source, &mut error_reporter);
+        let tokens = scanner.scan_tokens();
+        let correct = vec![
+            Token {
+                typ: PlusEqual,
+                lexeme: "+=".to_string(),
+                line: 1,
+                column: 1,
+                visual_column: 1,
+                start: 0,
+                end: 2,
+            },
+            Token {
+                typ: MinusEqual,
+                lexeme: "-=".to_string(),
+                line: 1,
+                column: 4,
+                visual_column: 4,
+                start: 3,
+                end: 5,
+            },
+            Token {
+                typ: StarEqual,
+                lexeme: "*=".to_string(),
+                line: 1,
+                column: 7,
+                visual_column: 7,
+                start: 6,
+                end: 8,
+            },
+            Token {
+                typ: SlashEqual,
+                lexeme: "/=".to_string(),
+                line: 1,
+                column: 10,
+                visual_column: 10,
+                start: 9,
+                end: 11,
+            },
+            Token {
+                typ: Plus,
+                lexeme: "+".to_string(),
+                line: 1,
+                column: 13,
+                visual_column: 13,
+                start: 12,
+                end: 13,
+            },
+            Token {
+                typ: Minus,
+                lexeme: "-".to_string(),
+                line: 1,
+                column: 15,
+                visual_column: 15,
+                start: 14,
+                end: 15,
+            },
+            Token {
+                typ: Star,
+                lexeme: "*".to_string(),
+                line: 1,
+                column: 17,
+                visual_column: 17,
+                start: 16,
+                end: 17,
+            },
+            Token {
+                typ: Slash,
+                lexeme: "/".to_string(),
+                line: 1,
+                column: 19,
+                visual_column: 19,
+                start: 18,
+                end: 19,
+            },
+            Token {
+                typ: EOF,
+                lexeme: "".to_string(),
+                line: 1,
+                column: 20,
+                visual_column: 20,
+                start: 19,
+                end: 19,
+            },
+        ];
+
+        assert_eq!(tokens, correct);
+    }
+
+    #[test]
+    fn scan_exponents() {
+        let source = "1e3 2.5E-2".to_string();
+        let mut error_reporter = ErrorReporter::new();
+
+        let scanner = Scanner::new(source, &mut error_reporter);
+        let tokens = scanner.scan_tokens();
+        let correct = vec![
+            Token {
+                typ: Number(1000.0),
+                lexeme: "1e3".to_string(),
+                line: 1,
+                column: 1,
+                visual_column: 1,
+                start: 0,
+                end: 3,
+            },
+            Token {
+                typ: Number(0.025),
+                lexeme: "2.5E-2".to_string(),
+                line: 1,
+                column: 5,
+                visual_column: 5,
+                start: 4,
+                end: 10,
+            },
+            Token {
+                typ: EOF,
+                lexeme: "".to_string(),
+                line: 1,
+                column: 11,
+                visual_column: 11,
+                start: 10,
+                end: 10,
+            },
+        ];
+
+        assert_eq!(tokens, correct);
+    }
+
+    #[test]
+    fn scan_exponent_without_digits() {
+        let source = "3e".to_string();
+        let mut error_reporter = ErrorReporter::new();
+
+        let scanner = Scanner::new(source, &mut error_reporter);
+        scanner.scan_tokens();
+
+        assert!(error_reporter.had_error);
+    }
+
+    #[test]
+    fn scan_hex_numbers() {
+        let source = "0xff 0X1b".to_string();
+        let mut error_reporter = ErrorReporter::new();
+
+        let scanner = Scanner::new(source, &mut error_reporter);
+        let tokens = scanner.scan_tokens();
+        let correct = vec![
+            Token {
+                typ: Number(255.0),
+                lexeme: "0xff".to_string(),
+                line: 1,
+                column: 1,
+                visual_column: 1,
+                start: 0,
+                end: 4,
+            },
+            Token {
+                typ: Number(27.0),
+                lexeme: "0X1b".to_string(),
+                line: 1,
+                column: 6,
+                visual_column: 6,
+                start: 5,
+                end: 9,
+            },
+            Token {
+                typ: EOF,
+                lexeme: "".to_string(),
+                line: 1,
+                column: 10,
+                visual_column: 10,
+                start: 9,
+                end: 9,
+            },
+        ];
+
+        assert_eq!(tokens, correct);
+    }
+
+    #[test]
+    fn scan_hex_prefix_without_digits() {
+        let source = "0x;".to_string();
+        let mut error_reporter = ErrorReporter::new();
+
+        let scanner = Scanner::new(source, &mut error_reporter);
+        scanner.scan_tokens();
+
+        assert!(error_reporter.had_error);
+    }
+
+    #[test]
+    // 3.14_15 is the literal under test, not an approximation of PI.
+    #[allow(clippy::approx_constant)]
+    fn scan_digit_separators() {
+        let source = "1_000 3.14_15".to_string();
+        let mut error_reporter = ErrorReporter::new();
+
+        let scanner = Scanner::new(source, &mut error_reporter);
+        let tokens = scanner.scan_tokens();
+        let correct = vec![
+            Token {
+                typ: Number(1000.0),
+                lexeme: "1_000".to_string(),
+                line: 1,
+                column: 1,
+                visual_column: 1,
+                start: 0,
+                end: 5,
+            },
+            Token {
+                typ: Number(3.1415),
+                lexeme: "3.14_15".to_string(),
+                line: 1,
+                column: 7,
+                visual_column: 7,
+                start: 6,
+                end: 13,
+            },
+            Token {
+                typ: EOF,
+                lexeme: "".to_string(),
+                line: 1,
+                column: 14,
+                visual_column: 14,
+                start: 13,
+                end: 13,
+            },
+        ];
+
+        assert_eq!(tokens, correct);
+    }
+
+    #[test]
+    fn scan_leading_underscore_as_identifier() {
+        let source = "_1".to_string();
+        let mut error_reporter = ErrorReporter::new();
+
+        let scanner = Scanner::new(source, &mut error_reporter);
+        let tokens = scanner.scan_tokens();
+        let correct = vec![
+            Token {
+                typ: Identifier("_1".to_string()),
+                lexeme: "_1".to_string(),
+                line: 1,
+                column: 1,
+                visual_column: 1,
+                start: 0,
+                end: 2,
+            },
+            Token {
+                typ: EOF,
+                lexeme: "".to_string(),
+                line: 1,
+                column: 3,
+                visual_column: 3,
+                start: 2,
+                end: 2,
+            },
+        ];
+
+        assert_eq!(tokens, correct);
+    }
+
+    #[test]
+    fn scan_doubled_digit_separator() {
+        let source = "1__0".to_string();
+        let mut error_reporter = ErrorReporter::new();
+
+        let scanner = Scanner::new(source, &mut error_reporter);
+        scanner.scan_tokens();
+
+        assert!(error_reporter.had_error);
+    }
+
+    #[test]
+    fn scan_digit_separator_before_dot() {
+        let source = "1_.0".to_string();
+        let mut error_reporter = ErrorReporter::new();
+
+        let scanner = Scanner::new(source, &mut error_reporter);
+        scanner.scan_tokens();
+
+        assert!(error_reporter.had_error);
+    }
+
+    #[test]
+    fn scan_crlf_line_endings() {
+        let source = "print \"a\r\nb\";\r\nnil;".to_string();
+        let mut error_reporter = ErrorReporter::new();
+
+        let scanner = Scanner::new(source, &mut error_reporter);
+        let tokens = scanner.scan_tokens();
+        let correct = vec![
+            Token {
+                typ: Print,
+                lexeme: "print".to_string(),
+                line: 1,
+                column: 1,
+                visual_column: 1,
+                start: 0,
+                end: 5,
+            },
+            Token {
+                typ: String("a\nb".to_string()),
+                lexeme: "\"a\r\nb\"".to_string(),
+                line: 2,
+                column: 7,
+                visual_column: 7,
+                start: 6,
+                end: 12,
+            },
+            Token {
+                typ: SemiColon,
+                lexeme: ";".to_string(),
+                line: 2,
+                column: 3,
+                visual_column: 3,
+                start: 12,
+                end: 13,
+            },
+            Token {
+                typ: Nil,
+                lexeme: "nil".to_string(),
+                line: 3,
+                column: 1,
+                visual_column: 1,
+                start: 15,
+                end: 18,
+            },
+            Token {
+                typ: SemiColon,
+                lexeme: ";".to_string(),
+                line: 3,
+                column: 4,
+                visual_column: 4,
+                start: 18,
+                end: 19,
+            },
+            Token {
+                typ: EOF,
+                lexeme: "".to_string(),
+                line: 3,
+                column: 5,
+                visual_column: 5,
+                start: 19,
+                end: 19,
+            },
+        ];
+
+        assert_eq!(tokens, correct);
+    }
+
+    #[test]
+    fn scan_unicode_identifier() {
+        let source = "var π = 3; café".to_string();
+        let mut error_reporter = ErrorReporter::new();
+
+        let scanner = Scanner::new(source, &mut error_reporter);
+        let tokens = scanner.scan_tokens();
+        let correct = vec![
+            Token {
+                typ: Var,
+                lexeme: "var".to_string(),
+                line: 1,
+                column: 1,
+                visual_column: 1,
+                start: 0,
+                end: 3,
+            },
+            Token {
+                typ: Identifier("π".to_string()),
+                lexeme: "π".to_string(),
+                line: 1,
+                column: 5,
+                visual_column: 5,
+                start: 4,
+                end: 5,
+            },
+            Token {
+                typ: Equal,
+                lexeme: "=".to_string(),
+                line: 1,
+                column: 7,
+                visual_column: 7,
+                start: 6,
+                end: 7,
+            },
+            Token {
+                typ: Number(3.0),
+                lexeme: "3".to_string(),
+                line: 1,
+                column: 9,
+                visual_column: 9,
+                start: 8,
+                end: 9,
+            },
+            Token {
+                typ: SemiColon,
+                lexeme: ";".to_string(),
+                line: 1,
+                column: 10,
+                visual_column: 10,
+                start: 9,
+                end: 10,
+            },
+            Token {
+                typ: Identifier("café".to_string()),
+                lexeme: "café".to_string(),
+                line: 1,
+                column: 12,
+                visual_column: 12,
+                start: 11,
+                end: 15,
+            },
+            Token {
+                typ: EOF,
+                lexeme: "".to_string(),
+                line: 1,
+                column: 16,
+                visual_column: 16,
+                start: 15,
+                end: 15,
+            },
+        ];
+
+        assert_eq!(tokens, correct);
+    }
+
+    #[test]
+    fn scan_comparators() {
+        let source = "1 == 2; 1 != 2; 1 < 2; 1 <= 2; 1 > 2; 1 >= 2;".to_string();
+        let mut error_reporter = ErrorReporter::new();
+
+        let scanner = Scanner::new(source, &mut error_reporter);
+        let tokens = scanner.scan_tokens();
+        let correct = vec![
+            // 1 == 2;
+            Token {
+                typ: Number(1.0),
+                lexeme: "1".to_string(),
+                line: 1,
+                column: 1,
+                visual_column: 1,
+                start: 0,
+                end: 1,
             },
             Token {
                 typ: EqualEqual,
                 lexeme: "==".to_string(),
                 line: 1,
+                column: 3,
+                visual_column: 3,
+                start: 2,
+                end: 4,
             },
             Token {
                 typ: Number(2.0),
                 lexeme: "2".to_string(),
                 line: 1,
+                column: 6,
+                visual_column: 6,
+                start: 5,
+                end: 6,
             },
             Token {
                 typ: SemiColon,
                 lexeme: ";".to_string(),
                 line: 1,
+                column: 7,
+                visual_column: 7,
+                start: 6,
+                end: 7,
             },
             // 1 != 2;
             Token {
                 typ: Number(1.0),
                 lexeme: "1".to_string(),
                 line: 1,
+                column: 9,
+                visual_column: 9,
+                start: 8,
+                end: 9,
             },
             Token {
                 typ: BangEqual,
                 lexeme: "!=".to_string(),
                 line: 1,
+                column: 11,
+                visual_column: 11,
+                start: 10,
+                end: 12,
             },
             Token {
                 typ: Number(2.0),
                 lexeme: "2".to_string(),
                 line: 1,
+                column: 14,
+                visual_column: 14,
+                start: 13,
+                end: 14,
             },
             Token {
                 typ: SemiColon,
                 lexeme: ";".to_string(),
                 line: 1,
+                column: 15,
+                visual_column: 15,
+                start: 14,
+                end: 15,
             },
             // 1 < 2;
             Token {
                 typ: Number(1.0),
                 lexeme: "1".to_string(),
                 line: 1,
+                column: 17,
+                visual_column: 17,
+                start: 16,
+                end: 17,
             },
             Token {
                 typ: Less,
                 lexeme: "<".to_string(),
                 line: 1,
+                column: 19,
+                visual_column: 19,
+                start: 18,
+                end: 19,
             },
             Token {
                 typ: Number(2.0),
                 lexeme: "2".to_string(),
                 line: 1,
+                column: 21,
+                visual_column: 21,
+                start: 20,
+                end: 21,
             },
             Token {
                 typ: SemiColon,
                 lexeme: ";".to_string(),
                 line: 1,
+                column: 22,
+                visual_column: 22,
+                start: 21,
+                end: 22,
             },
             // 1 <= 2;
             Token {
                 typ: Number(1.0),
                 lexeme: "1".to_string(),
                 line: 1,
+                column: 24,
+                visual_column: 24,
+                start: 23,
+                end: 24,
             },
             Token {
                 typ: LessEqual,
                 lexeme: "<=".to_string(),
                 line: 1,
+                column: 26,
+                visual_column: 26,
+                start: 25,
+                end: 27,
             },
             Token {
                 typ: Number(2.0),
                 lexeme: "2".to_string(),
                 line: 1,
+                column: 29,
+                visual_column: 29,
+                start: 28,
+                end: 29,
             },
             Token {
                 typ: SemiColon,
                 lexeme: ";".to_string(),
                 line: 1,
+                column: 30,
+                visual_column: 30,
+                start: 29,
+                end: 30,
             },
             // 1 > 2;
             Token {
                 typ: Number(1.0),
                 lexeme: "1".to_string(),
                 line: 1,
+                column: 32,
+                visual_column: 32,
+                start: 31,
+                end: 32,
             },
             Token {
                 typ: Greater,
                 lexeme: ">".to_string(),
                 line: 1,
+                column: 34,
+                visual_column: 34,
+                start: 33,
+                end: 34,
             },
             Token {
                 typ: Number(2.0),
                 lexeme: "2".to_string(),
                 line: 1,
+                column: 36,
+                visual_column: 36,
+                start: 35,
+                end: 36,
             },
             Token {
                 typ: SemiColon,
                 lexeme: ";".to_string(),
                 line: 1,
+                column: 37,
+                visual_column: 37,
+                start: 36,
+                end: 37,
             },
             // 1 >= 2;
             Token {
                 typ: Number(1.0),
                 lexeme: "1".to_string(),
                 line: 1,
+                column: 39,
+                visual_column: 39,
+                start: 38,
+                end: 39,
             },
             Token {
                 typ: GreaterEqual,
                 lexeme: ">=".to_string(),
                 line: 1,
+                column: 41,
+                visual_column: 41,
+                start: 40,
+                end: 42,
             },
             Token {
                 typ: Number(2.0),
                 lexeme: "2".to_string(),
                 line: 1,
+                column: 44,
+                visual_column: 44,
+                start: 43,
+                end: 44,
             },
             Token {
                 typ: SemiColon,
                 lexeme: ";".to_string(),
                 line: 1,
+                column: 45,
+                visual_column: 45,
+                start: 44,
+                end: 45,
+            },
+            Token {
+                typ: EOF,
+                lexeme: "".to_string(),
+                line: 1,
+                column: 46,
+                visual_column: 46,
+                start: 45,
+                end: 45,
             },
         ];
 
-        tokens
-            .into_iter()
-            .zip(correct)
-            .for_each(|(a, b)| assert_eq!(a, b));
+        assert_eq!(tokens, correct);
     }
 }
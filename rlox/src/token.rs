@@ -1,21 +1,23 @@
 use std::fmt::Display;
 
 use phf::phf_map;
+use serde::Serialize;
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub enum TokenType {
     // Single-character tokens.
     LeftParen,
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
     Comma,
     Dot,
-    Minus,
-    Plus,
     SemiColon,
-    Slash,
-    Star,
+    Percent,
+    Question,
+    Colon,
 
     // One or two character tokens.
     Bang,
@@ -26,15 +28,39 @@ pub enum TokenType {
     GreaterEqual,
     Less,
     LessEqual,
+    Minus,
+    MinusEqual,
+    MinusMinus,
+    Plus,
+    PlusEqual,
+    PlusPlus,
+    // Nil coalescing.
+    QuestionQuestion,
+    Slash,
+    SlashEqual,
+    Star,
+    StarEqual,
+    StarStar,
+    // Floor division. `//` already starts a comment, so this borrows Dart's
+    // `~/` instead.
+    TildeSlash,
 
     // Literals.
     Identifier(String),
     String(String),
-    Number(f32),
+    Number(f64),
+
+    // The text of a comment without its delimiters. Only produced when the
+    // scanner is asked to keep comments.
+    Comment(String),
 
     // Keywords.
     And,
+    Break,
+    Case,
     Class,
+    Continue,
+    Default,
     Else,
     False,
     Fun,
@@ -45,6 +71,7 @@ pub enum TokenType {
     Print,
     Return,
     Super,
+    Switch,
     This,
     True,
     Var,
@@ -55,7 +82,11 @@ pub enum TokenType {
 
 pub static KEYWORDS: phf::Map<&'static str, TokenType> = phf_map! {
     "and" => TokenType::And,
+    "break" => TokenType::Break,
+    "case" => TokenType::Case,
     "class" => TokenType::Class,
+    "continue" => TokenType::Continue,
+    "default" => TokenType::Default,
     "else" => TokenType::Else,
     "false" => TokenType::False,
     "for" => TokenType::For,
@@ -66,22 +97,48 @@ pub static KEYWORDS: phf::Map<&'static str, TokenType> = phf_map! {
     "print" => TokenType::Print,
     "return" => TokenType::Return,
     "super" => TokenType::Super,
+    "switch" => TokenType::Switch,
     "this" => TokenType::This,
     "true" => TokenType::True,
     "var" => TokenType::Var,
     "while" => TokenType::While,
 };
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub struct Token {
     pub typ: TokenType,
     pub lexeme: String,
     pub line: i32,
+    // 1-based column of the token's first character within its line.
+    pub column: usize,
+    // Where `column` is displayed once tabs before it are expanded.
+    pub visual_column: usize,
+    // Character offsets into the whole source: `start` is the token's first
+    // character and `end` is one past its last, so `end - start` is the
+    // lexeme's length.
+    pub start: usize,
+    pub end: usize,
 }
 
 impl Token {
-    pub fn new(typ: TokenType, lexeme: String, line: i32) -> Token {
-        Token { typ, lexeme, line }
+    pub fn new(
+        typ: TokenType,
+        lexeme: String,
+        line: i32,
+        column: usize,
+        visual_column: usize,
+        start: usize,
+        end: usize,
+    ) -> Token {
+        Token {
+            typ,
+            lexeme,
+            line,
+            column,
+            visual_column,
+            start,
+            end,
+        }
     }
 }
 
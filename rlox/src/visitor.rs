@@ -1,9 +1,47 @@
-use crate::grammar::{BinaryExpr, Expr, GroupingExpr, LiteralExpr, UnaryExpr};
+use std::rc::Rc;
+
+use crate::grammar::{
+    AssignExpr, BinaryExpr, BlockStmt, BreakStmt, CallExpr, ClassStmt, ContinueStmt, Expr,
+    ExpressionStmt, FunctionExpr, FunctionStmt, GetExpr, GroupingExpr, IfStmt, IndexExpr, ListExpr,
+    LiteralExpr, LogicalExpr, PrintStmt, ReturnStmt, SetExpr, Stmt, SuperExpr, SwitchStmt,
+    TernaryExpr, ThisExpr, UnaryExpr, UpdateExpr, VarStmt, VariableExpr, WhileStmt,
+};
 
 pub trait Visitor<T> {
-    fn visit_expr(&self, expr: &Expr) -> T;
-    fn visit_literal_expr(&self, expr: &LiteralExpr) -> T;
-    fn visit_unary_expr(&self, expr: &UnaryExpr) -> T;
-    fn visit_binary_expr(&self, expr: &BinaryExpr) -> T;
-    fn visit_grouping_expr(&self, expr: &GroupingExpr) -> T;
+    fn visit_expr(&mut self, expr: &Expr) -> T;
+    fn visit_literal_expr(&mut self, expr: &LiteralExpr) -> T;
+    fn visit_unary_expr(&mut self, expr: &UnaryExpr) -> T;
+    fn visit_binary_expr(&mut self, expr: &BinaryExpr) -> T;
+    fn visit_grouping_expr(&mut self, expr: &GroupingExpr) -> T;
+    fn visit_variable_expr(&mut self, expr: &VariableExpr) -> T;
+    fn visit_assign_expr(&mut self, expr: &AssignExpr) -> T;
+    fn visit_logical_expr(&mut self, expr: &LogicalExpr) -> T;
+    fn visit_call_expr(&mut self, expr: &CallExpr) -> T;
+    fn visit_get_expr(&mut self, expr: &GetExpr) -> T;
+    fn visit_set_expr(&mut self, expr: &SetExpr) -> T;
+    fn visit_this_expr(&mut self, expr: &ThisExpr) -> T;
+    fn visit_super_expr(&mut self, expr: &SuperExpr) -> T;
+    fn visit_ternary_expr(&mut self, expr: &TernaryExpr) -> T;
+    fn visit_function_expr(&mut self, expr: &FunctionExpr) -> T;
+    fn visit_list_expr(&mut self, expr: &ListExpr) -> T;
+    fn visit_index_expr(&mut self, expr: &IndexExpr) -> T;
+    fn visit_update_expr(&mut self, expr: &UpdateExpr) -> T;
+}
+
+// The statement counterpart of `Visitor`, kept separate because consumers
+// usually produce different results for statements than for expressions.
+pub trait StmtVisitor<T> {
+    fn visit_stmt(&mut self, stmt: &Stmt) -> T;
+    fn visit_expression_stmt(&mut self, stmt: &ExpressionStmt) -> T;
+    fn visit_print_stmt(&mut self, stmt: &PrintStmt) -> T;
+    fn visit_var_stmt(&mut self, stmt: &VarStmt) -> T;
+    fn visit_block_stmt(&mut self, stmt: &BlockStmt) -> T;
+    fn visit_if_stmt(&mut self, stmt: &IfStmt) -> T;
+    fn visit_while_stmt(&mut self, stmt: &WhileStmt) -> T;
+    fn visit_function_stmt(&mut self, stmt: &Rc<FunctionStmt>) -> T;
+    fn visit_return_stmt(&mut self, stmt: &ReturnStmt) -> T;
+    fn visit_break_stmt(&mut self, stmt: &BreakStmt) -> T;
+    fn visit_continue_stmt(&mut self, stmt: &ContinueStmt) -> T;
+    fn visit_class_stmt(&mut self, stmt: &ClassStmt) -> T;
+    fn visit_switch_stmt(&mut self, stmt: &SwitchStmt) -> T;
 }
@@ -0,0 +1,76 @@
+use std::{
+    fs,
+    process::{Command, Output},
+};
+
+fn run_script(name: &str, source: &str, flags: &[&str]) -> Output {
+    let path = std::env::temp_dir().join(name);
+    fs::write(&path, source).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_rlox"))
+        .args(flags)
+        .arg(&path)
+        .output()
+        .unwrap();
+    fs::remove_file(&path).unwrap();
+    output
+}
+
+#[test]
+fn runtime_error_exits_with_dataerr() {
+    let output = run_script(
+        "rlox_runtime_error_exits_with_dataerr.lox",
+        "print -\"a\";",
+        &[],
+    );
+
+    assert_eq!(output.status.code(), Some(exitcode::DATAERR));
+}
+
+#[test]
+fn successful_script_exits_with_ok() {
+    let output = run_script("rlox_successful_script_exits_with_ok.lox", "print 1;", &[]);
+
+    assert_eq!(output.status.code(), Some(exitcode::OK));
+}
+
+#[test]
+fn check_passes_valid_script_without_running_it() {
+    let output = run_script(
+        "rlox_check_passes_valid_script_without_running_it.lox",
+        "var a = 1; print a; print -\"a\";",
+        &["--check"],
+    );
+
+    assert_eq!(output.status.code(), Some(exitcode::OK));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn check_fails_broken_script() {
+    let output = run_script(
+        "rlox_check_fails_broken_script.lox",
+        "print 1;\nvar a = ;",
+        &["--check"],
+    );
+
+    assert_eq!(output.status.code(), Some(exitcode::DATAERR));
+    assert!(output.stdout.is_empty());
+}
+
+#[test]
+fn time_reports_each_phase() {
+    let output = run_script(
+        "rlox_time_reports_each_phase.lox",
+        "print clock() > 0;",
+        &["--time"],
+    );
+
+    assert_eq!(output.status.code(), Some(exitcode::OK));
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "true\n");
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let phases = stderr
+        .lines()
+        .map(|line| line.split(':').next().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(phases, vec!["scan", "parse", "interpret"]);
+}
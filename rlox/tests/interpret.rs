@@ -0,0 +1,60 @@
+use rlox::{
+    error_reporter::{ErrorReporter, Severity},
+    interpreter::Interpreter,
+};
+
+#[test]
+fn interpret_valid_program() {
+    assert_eq!(rlox::interpret("var a = 1; var b = a + 2;"), Ok(()));
+}
+
+#[test]
+fn interpret_reports_parse_errors() {
+    let diagnostics = rlox::interpret("var = 1;\nprint (2;").unwrap_err();
+
+    let lines = diagnostics.iter().map(|d| d.line).collect::<Vec<_>>();
+    assert_eq!(lines, vec![1, 2]);
+    assert!(diagnostics.iter().all(|d| d.severity == Severity::Error));
+}
+
+#[test]
+fn interpret_reports_every_scan_error() {
+    let diagnostics = rlox::interpret("print @;\nvar a = # 1 $;").unwrap_err();
+
+    let reported = diagnostics
+        .iter()
+        .map(|d| (d.line, d.column, d.message.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        reported,
+        vec![
+            (1, Some(7), "Unrecognized character: @"),
+            (2, Some(9), "Unrecognized character: #"),
+            (2, Some(13), "Unrecognized character: $"),
+        ]
+    );
+}
+
+#[test]
+fn interpret_reports_runtime_error() {
+    let diagnostics = rlox::interpret("var a = 1;\nprint missing;").unwrap_err();
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].line, 2);
+    assert_eq!(diagnostics[0].message, "Undefined variable 'missing'.");
+}
+
+#[test]
+fn unreachable_code_warns_but_still_runs() {
+    let mut error_reporter = ErrorReporter::with_output(Box::new(std::io::sink()));
+    let source = "fun f() {\n  return 1;\n  print 2;\n}\nprint f();";
+    let mut interpreter = Interpreter::with_output(Box::new(std::io::sink()));
+    let exit_code = rlox::run(source, &mut error_reporter, &mut interpreter);
+
+    assert_eq!(exit_code, exitcode::OK);
+    let diagnostics = error_reporter.diagnostics();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+    assert_eq!(diagnostics[0].line, 3);
+    assert_eq!(diagnostics[0].message, "Unreachable code after return.");
+}
@@ -0,0 +1,16 @@
+use rlox::{error_reporter::ErrorReporter, grammar::Literal, interpreter::Interpreter};
+
+#[test]
+fn repl_prints_expressions_and_keeps_state() {
+    let mut error_reporter = ErrorReporter::with_output(Box::new(std::io::sink()));
+    let mut interpreter = Interpreter::new();
+
+    assert_eq!(
+        rlox::run_line("var x = 1;", &mut error_reporter, &mut interpreter),
+        Ok(None)
+    );
+    assert_eq!(
+        rlox::run_line("x + 1", &mut error_reporter, &mut interpreter),
+        Ok(Some(Literal::Number(2.0)))
+    );
+}
@@ -0,0 +1,30 @@
+use std::io;
+
+use rlox::error_reporter::ErrorReporter;
+
+#[test]
+fn scan_lists_tokens() {
+    let mut error_reporter = ErrorReporter::with_output(Box::new(io::sink()));
+    let tokens = rlox::scan("var x = 1;", &mut error_reporter).unwrap();
+
+    let lines = tokens.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+    assert_eq!(
+        lines,
+        vec![
+            "<TokenType::Var, var>",
+            "<TokenType::Identifier(\"x\"), x, x>",
+            "<TokenType::Equal, =>",
+            "<TokenType::Number(1.0), 1, 1>",
+            "<TokenType::SemiColon, ;>",
+            "<TokenType::EOF, >",
+        ]
+    );
+}
+
+#[test]
+fn scan_stops_at_lexical_errors() {
+    let mut error_reporter = ErrorReporter::with_output(Box::new(io::sink()));
+
+    assert_eq!(rlox::scan("var @ = 1;", &mut error_reporter), None);
+    assert!(error_reporter.had_error);
+}